@@ -0,0 +1,18 @@
+-- Saved searches evaluated against reward_flights_latest
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    carrier_code TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    departure_from DATE NOT NULL,
+    departure_to DATE NOT NULL,
+    cabin_type TEXT NOT NULL,
+    min_seats INTEGER NOT NULL DEFAULT 1,
+    max_points INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT saved_searches_departure_window CHECK (departure_from <= departure_to)
+);
+
+CREATE INDEX IF NOT EXISTS saved_searches_carrier_code_idx ON saved_searches (carrier_code);
@@ -0,0 +1,41 @@
+-- Webhook notified when a saved search gains matches, signed with its secret
+ALTER TABLE saved_searches ADD COLUMN IF NOT EXISTS webhook_url TEXT;
+ALTER TABLE saved_searches ADD COLUMN IF NOT EXISTS webhook_secret TEXT;
+
+-- Flight, cabin and price combinations already sent to a saved search's webhook
+CREATE TABLE IF NOT EXISTS webhook_notified_prices (
+    saved_search_id BIGINT NOT NULL REFERENCES saved_searches (id) ON DELETE CASCADE,
+    origin TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    departure DATE NOT NULL,
+    cabin_type TEXT NOT NULL,
+    points INTEGER NOT NULL,
+    notified_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (saved_search_id, origin, destination, departure, cabin_type, points)
+);
+
+-- Every webhook delivery, successful or not, after its final attempt
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    saved_search_id BIGINT NOT NULL REFERENCES saved_searches (id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    response_status INTEGER,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS webhook_deliveries_saved_search_id_idx ON webhook_deliveries (saved_search_id, created_at DESC);
+
+-- Deliveries that failed every attempt, kept after their saved search is deleted
+CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+    id BIGSERIAL PRIMARY KEY,
+    delivery_id BIGINT NOT NULL,
+    saved_search_id BIGINT NOT NULL,
+    url TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -1,10 +1,17 @@
-use actix_web::{web, App, HttpResponse, HttpServer, Responder, get};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder, ResponseError, delete, get, post, put};
+use actix_web::http::StatusCode;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc, NaiveDate};
+use chrono::{DateTime, Datelike, DurationRound, Utc, NaiveDate, Weekday};
 use sqlx::{Pool, Postgres, Row};
+use sqlx::postgres::PgRow;
 use dotenv::dotenv;
 use log::info;
 use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
+use futures_util::stream::BoxStream;
+use utoipa::{IntoParams, IntoResponses, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+use std::collections::HashMap;
 
 
 /// # Rewardo Search API
@@ -13,16 +20,27 @@ use async_trait::async_trait;
 /// It implements a Spring Controller API that was provided in the requirements.
 ///
 /// The main endpoint is:
-/// GET /origin/{origin}/destination/{destination}/from/{from}/to/{to}
+/// GET /api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/from/{from}/to/{to}
+///
+/// The carrier must be registered in the carrier registry, otherwise a 404 is returned.
 ///
 /// Query parameters:
 /// - page-number: The page number for pagination (default: 0)
 /// - page-size: The number of items per page (default: 10)
 ///
 /// The API returns a paginated list of reward flights matching the criteria.
+///
+/// Bulk consumers can stream a carrier's latest reward flights as newline-delimited JSON from
+/// GET /api/v1/airline/{carrier}/reward-flights/export
+///
+/// The OpenAPI 3 specification is served at /openapi.json, browsable at /swagger-ui/.
+///
+/// Errors are returned as RFC 7807 problem details (`application/problem+json`) with a
+/// `type`, `title`, `status`, `detail` and the `errors` of any invalid fields.
 
 // Models copied from rewardo-virgin-scraper
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(description = "The latest scraped award availability of a flight")]
 pub struct RewardFlightLatest {
     pub id: Option<String>,
     pub origin: String,
@@ -34,10 +52,69 @@ pub struct RewardFlightLatest {
     pub award_business: Option<AwardBusiness>,
     pub award_premium_economy: Option<AwardPremiumEconomy>,
     pub award_first: Option<AwardFirst>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deal_scores: Option<DealScores>,
+}
+
+impl RewardFlightLatest {
+    /// Whether both are the same flight: the same route, departure and carrier
+    pub fn is_same_flight(&self, other: &RewardFlightLatest) -> bool {
+        self.origin == other.origin
+            && self.destination == other.destination
+            && self.departure == other.departure
+            && self.carrier_code == other.carrier_code
+    }
+
+    /// Whether both have the same awards in every cabin, ignoring when they were scraped
+    pub fn has_same_availability(&self, other: &RewardFlightLatest) -> bool {
+        self.award_economy == other.award_economy
+            && self.award_premium_economy == other.award_premium_economy
+            && self.award_business == other.award_business
+            && self.award_first == other.award_first
+    }
+}
+
+// The awards of each cabin, shared by the latest flights and their historic snapshots
+pub trait CabinAwards {
+    /// The points price, saver flag and seats left of the given cabin's award, if there is an award for it
+    fn award(&self, cabin_type: CabinType) -> Option<(Option<i32>, Option<bool>, Option<i32>)>;
+
+    /// The points price of the given cabin, if there is an award for it
+    fn cabin_points_value(&self, cabin_type: CabinType) -> Option<i32> {
+        self.award(cabin_type).and_then(|(points, _, _)| points)
+    }
+
+    /// Whether the given cabin's award is a saver award, if there is an award for it
+    fn is_saver_award(&self, cabin_type: CabinType) -> Option<bool> {
+        self.award(cabin_type).and_then(|(_, saver, _)| saver)
+    }
+
+    /// The seats left in the given cabin, if there is an award for it
+    fn cabin_class_seat_count(&self, cabin_type: CabinType) -> Option<i32> {
+        self.award(cabin_type).and_then(|(_, _, seats)| seats)
+    }
+}
+
+/// Implements `CabinAwards` for structs with an `award_*` field per cabin
+macro_rules! impl_cabin_awards {
+    ($($flight:ty),+) => {$(
+        impl CabinAwards for $flight {
+            fn award(&self, cabin_type: CabinType) -> Option<(Option<i32>, Option<bool>, Option<i32>)> {
+                match cabin_type {
+                    CabinType::Economy => self.award_economy.as_ref().map(|award| (award.cabin_points_value, award.is_saver_award, award.cabin_class_seat_count)),
+                    CabinType::PremiumEconomy => self.award_premium_economy.as_ref().map(|award| (award.cabin_points_value, award.is_saver_award, award.cabin_class_seat_count)),
+                    CabinType::Business => self.award_business.as_ref().map(|award| (award.cabin_points_value, award.is_saver_award, award.cabin_class_seat_count)),
+                    CabinType::First => self.award_first.as_ref().map(|award| (award.cabin_points_value, award.is_saver_award, award.cabin_class_seat_count)),
+                }
+            }
+        }
+    )+};
 }
 
+impl_cabin_awards!(RewardFlightLatest, RewardFlightLatestHistoric);
+
 // Historic reward flight model
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct RewardFlightLatestHistoric {
     pub id: Option<String>,
     pub origin: String,
@@ -51,7 +128,7 @@ pub struct RewardFlightLatestHistoric {
     pub award_first: Option<AwardFirst>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct AwardEconomy {
     pub id: Option<String>,
     pub cabin_points_value: Option<i32>,
@@ -60,7 +137,7 @@ pub struct AwardEconomy {
     pub cabin_class_seat_count_string: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct AwardBusiness {
     pub id: Option<String>,
     pub cabin_points_value: Option<i32>,
@@ -69,7 +146,7 @@ pub struct AwardBusiness {
     pub cabin_class_seat_count_string: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct AwardPremiumEconomy {
     pub id: Option<String>,
     pub cabin_points_value: Option<i32>,
@@ -78,7 +155,7 @@ pub struct AwardPremiumEconomy {
     pub cabin_class_seat_count_string: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct AwardFirst {
     pub id: Option<String>,
     pub cabin_points_value: Option<i32>,
@@ -87,1166 +164,9371 @@ pub struct AwardFirst {
     pub cabin_class_seat_count_string: Option<String>,
 }
 
-// Pagination response wrapper
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Page<T> {
-    pub content: Vec<T>,
-    pub page_number: usize,
-    pub page_size: usize,
-    pub total_elements: i64,
-    pub total_pages: usize,
+// Outbound and return flights paired into a round trip (or an open-jaw itinerary)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RewardFlightRoundTrip {
+    pub outbound: RewardFlightLatest,
+    pub inbound: RewardFlightLatest,
+    pub outbound_cabin: CabinType,
+    pub return_cabin: CabinType,
+    pub trip_length_days: i32,
+    pub total_points: i32,
 }
 
-// Repository trait for RewardFlightLatest
-#[async_trait]
-pub trait RewardFlightRepository {
-    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
-        &self,
-        origin: &str,
-        destination: &str,
-        carrier_code: &str,
-        from_date: NaiveDate,
-        to_date: NaiveDate,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
-    
-    async fn find_all_ordered_by_lowest_cabin_points_and_origin_and_destination(
-        &self,
-        origin: &str,
-        destination: &str,
-        cabin_type: &str,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
-    
-    async fn find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
-        &self,
-        origin: &str,
-        destination: &str,
-        carrier_code: &str,
-        departure_date: NaiveDate,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error>;
+// An origin and destination airport pair
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CityPair {
+    pub origin: String,
+    pub destination: String,
 }
 
-// Database implementation of the repository
-pub struct RewardFlightLatestRepository {
-    pool: Pool<Postgres>,
+impl CityPair {
+    pub fn new(origin: &str, destination: &str) -> Self {
+        Self {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+        }
+    }
 }
 
-impl RewardFlightLatestRepository {
-    pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
-    }
+/// Splits city pairs into parallel origin and destination arrays for `UNNEST`
+fn city_pair_arrays(city_pairs: &[CityPair]) -> (Vec<String>, Vec<String>) {
+    city_pairs
+        .iter()
+        .map(|pair| (pair.origin.clone(), pair.destination.clone()))
+        .unzip()
 }
 
-#[async_trait]
-impl RewardFlightRepository for RewardFlightLatestRepository {
-    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
-        &self,
-        origin: &str,
-        destination: &str,
-        carrier_code: &str,
-        from_date: NaiveDate,
-        to_date: NaiveDate,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
-        // Calculate offset
-        let offset = (page_number * page_size) as i64;
-        
-        // Get total count using query_as instead of query_scalar! macro
-        let count_query = format!(
-            "SELECT COUNT(*) as count 
-            FROM reward_flights_latest rfl
-            WHERE rfl.origin = $1 
-            AND rfl.destination = $2 
-            AND rfl.carrier_code = $3 
-            AND rfl.departure::date BETWEEN $4 AND $5"
-        );
-        
-        info!("Executing count SQL query: {}", &count_query);
-        info!("Count query parameters: origin={}, destination={}, carrier_code={}, from_date={}, to_date={}", 
-            origin, destination, carrier_code, from_date, to_date);
-            
-        let count_result = sqlx::query_as::<_, (i64,)>(&count_query)
-            .bind(origin)
-            .bind(destination)
-            .bind(carrier_code)
-            .bind(from_date)
-            .bind(to_date)
-            .fetch_one(&self.pool)
-            .await;
-            
-        // Log the raw count SQL response
-        info!("Raw Count SQL Response: {:?}", count_result);
-        
-        let total_count: i64 = count_result
-            .map(|row| row.0)
-            .unwrap_or(0);
-            
-        info!("Count SQL Response: Total count = {}", total_count);
+// Criteria for pairing outbound and return flights
+#[derive(Debug, Clone)]
+pub struct RoundTripSearch {
+    pub outbound_cabin: CabinType,
+    pub return_cabin: CabinType,
+    pub outbound_from: NaiveDate,
+    pub outbound_to: NaiveDate,
+    pub return_from: NaiveDate,
+    pub return_to: NaiveDate,
+    pub min_trip_days: i32,
+    pub max_trip_days: i32,
+}
 
-        // Get paginated results using query_as instead of query! macro
-        let query = format!(
-            "SELECT 
-                rfl.id, 
-                rfl.origin, 
-                rfl.destination, 
-                rfl.departure, 
-                rfl.carrier_code, 
-                rfl.scraped_at,
-                ae.id as ae_id,
-                ae.cabin_points_value as ae_cabin_points_value,
-                ae.is_saver_award as ae_is_saver_award,
-                ae.cabin_class_seat_count as ae_cabin_class_seat_count,
-                ae.cabin_class_seat_count_string as ae_cabin_class_seat_count_string,
-                ab.id as ab_id,
-                ab.cabin_points_value as ab_cabin_points_value,
-                ab.is_saver_award as ab_is_saver_award,
-                ab.cabin_class_seat_count as ab_cabin_class_seat_count,
-                ab.cabin_class_seat_count_string as ab_cabin_class_seat_count_string,
-                ape.id as ape_id,
-                ape.cabin_points_value as ape_cabin_points_value,
-                ape.is_saver_award as ape_is_saver_award,
-                ape.cabin_class_seat_count as ape_cabin_class_seat_count,
-                ape.cabin_class_seat_count_string as ape_cabin_class_seat_count_string,
-                af.id as af_id,
-                af.cabin_points_value as af_cabin_points_value,
-                af.is_saver_award as af_is_saver_award,
-                af.cabin_class_seat_count as af_cabin_class_seat_count,
-                af.cabin_class_seat_count_string as af_cabin_class_seat_count_string
-            FROM reward_flights_latest rfl
-            LEFT JOIN award_economy ae ON ae.flight_id = rfl.id
-            LEFT JOIN award_business ab ON ab.flight_id = rfl.id
-            LEFT JOIN award_premium_economy ape ON ape.flight_id = rfl.id
-            LEFT JOIN award_first af ON af.flight_id = rfl.id
-            WHERE rfl.origin = $1 
-            AND rfl.destination = $2 
-            AND rfl.carrier_code = $3 
-            AND rfl.departure::date BETWEEN $4 AND $5
-            ORDER BY rfl.departure ASC
-            LIMIT $6 OFFSET $7"
-        );
-        
-        // Execute the query with all parameters
-        info!("Executing SQL query: {}", &query);
-        info!("Query parameters: origin={}, destination={}, carrier_code={}, from_date={}, to_date={}, limit={}, offset={}", 
-            origin, destination, carrier_code, from_date, to_date, page_size, offset);
-            
-        let rows = sqlx::query(&query)
-            .bind(origin)
-            .bind(destination)
-            .bind(carrier_code)
-            .bind(from_date)
-            .bind(to_date)
-            .bind(page_size as i64)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-            
-        info!("SQL Response: Found {} rows", rows.len());
-        
-        // Log the raw SQL response data
-        info!("Raw SQL Response: {:?}", rows);
-        
-        // Log the first row in detail to debug mapping issues
-        if let Some(first_row) = rows.first() {
-            println!("===== DEBUG: First row details =====");
-            println!("id as i32: {:?}", first_row.try_get::<Option<i32>, _>("id"));
-            println!("id as i64: {:?}", first_row.try_get::<Option<i64>, _>("id"));
-            println!("id as String: {:?}", first_row.try_get::<Option<String>, _>("id"));
-            println!("ae_id as i32: {:?}", first_row.try_get::<Option<i32>, _>("ae_id"));
-            println!("ae_id as i64: {:?}", first_row.try_get::<Option<i64>, _>("ae_id"));
-            println!("ae_id as String: {:?}", first_row.try_get::<Option<String>, _>("ae_id"));
-            println!("ab_id as i32: {:?}", first_row.try_get::<Option<i32>, _>("ab_id"));
-            println!("ape_id as i32: {:?}", first_row.try_get::<Option<i32>, _>("ape_id"));
-            println!("af_id as i32: {:?}", first_row.try_get::<Option<i32>, _>("af_id"));
-            println!("===== END DEBUG =====");
-        }
-
-        // Convert rows to RewardFlightLatest objects
-        let flights = rows
-            .into_iter()
-            .map(|row| {
-                // Construct award structs from joined columns
-                // Looking at the raw SQL response, we need to check if the ae_id column exists and has a value
-                let award_economy = match row.try_get::<i32, _>("ae_id") {
-                    Ok(id) => {
-                        // If we successfully got an ID, create the award struct
-                        Some(AwardEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ae_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ae_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ae_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ae_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_business = match row.try_get::<i32, _>("ab_id") {
-                    Ok(id) => {
-                        Some(AwardBusiness {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ab_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ab_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ab_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ab_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_premium_economy = match row.try_get::<i32, _>("ape_id") {
-                    Ok(id) => {
-                        Some(AwardPremiumEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ape_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ape_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ape_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ape_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_first = match row.try_get::<i32, _>("af_id") {
-                    Ok(id) => {
-                        Some(AwardFirst {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("af_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("af_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("af_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("af_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
+// Filters shared by the search endpoints
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub cabin_type: Option<CabinType>,
+    pub min_seats: Option<i32>,
+    pub saver_only: bool,
+    pub min_points: Option<i32>,
+    pub max_points: Option<i32>,
+    pub weekdays: Option<Vec<Weekday>>,
+}
 
-                // Get departure date and format it properly
-                let departure: Option<NaiveDate> = row.try_get("departure").ok().flatten();
-                let formatted_departure = departure.map_or_else(
-                    || String::new(), 
-                    |date| date.format("%Y-%m-%d").to_string()
-                );
-                
-                // Get the ID directly as i32 and convert to string
-                let id = match row.try_get::<i32, _>("id") {
-                    Ok(id) => Some(id.to_string()),
-                    Err(_) => None
-                };
-                
-                RewardFlightLatest {
-                    id,
-                    origin: row.try_get("origin").unwrap_or_default(),
-                    destination: row.try_get("destination").unwrap_or_default(),
-                    departure: formatted_departure,
-                    carrier_code: row.try_get("carrier_code").unwrap_or_default(),
-                    scraped_at: row.try_get("scraped_at").unwrap_or_else(|_| Utc::now()),
-                    award_economy,
-                    award_business,
-                    award_premium_economy,
-                    award_first,
+impl SearchFilter {
+    /// Whether any cabin-specific filter is set
+    fn has_cabin_filters(&self) -> bool {
+        self.min_seats.is_some() || self.saver_only || self.min_points.is_some() || self.max_points.is_some()
+    }
+
+    /// The cabins the cabin-specific filters apply to: the searched cabin if
+    /// there is one, else the filter's own cabin, else any cabin
+    fn cabins(&self, cabin_type: Option<CabinType>) -> Vec<CabinType> {
+        match cabin_type.or(self.cabin_type) {
+            Some(cabin_type) => vec![cabin_type],
+            None => CabinType::ALL.to_vec(),
+        }
+    }
+
+    /// Additional SQL conditions, each starting with `AND`, over the award table aliases
+    ///
+    /// A flight matches when one of the filtered cabins satisfies every cabin-specific filter.
+    pub fn sql_conditions(&self, cabin_type: Option<CabinType>) -> String {
+        if !self.has_cabin_filters() {
+            return String::new();
+        }
+
+        let any_cabin: Vec<String> = self
+            .cabins(cabin_type)
+            .iter()
+            .map(|cabin_type| {
+                let alias = cabin_type.award_alias();
+                let mut conditions = Vec::new();
+                if let Some(min_seats) = self.min_seats {
+                    conditions.push(format!("{}.cabin_class_seat_count >= {}", alias, min_seats));
                 }
+                if self.saver_only {
+                    conditions.push(format!("{}.is_saver_award = TRUE", alias));
+                }
+                if let Some(min_points) = self.min_points {
+                    conditions.push(format!("{}.cabin_points_value >= {}", alias, min_points));
+                }
+                if let Some(max_points) = self.max_points {
+                    conditions.push(format!("{}.cabin_points_value <= {}", alias, max_points));
+                }
+                format!("({})", conditions.join(" AND "))
             })
             .collect();
+        format!("\n            AND ({})", any_cabin.join(" OR "))
+    }
 
-        // Calculate total pages
-        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+    /// Additional SQL conditions, each starting with `AND`, on the departure of `reward_flights_latest rfl`
+    pub fn departure_sql_conditions(&self) -> String {
+        match &self.weekdays {
+            Some(weekdays) => {
+                let days: Vec<String> = weekdays
+                    .iter()
+                    .map(|weekday| weekday.number_from_monday().to_string())
+                    .collect();
+                format!("\n            AND EXTRACT(ISODOW FROM rfl.departure) IN ({})", days.join(", "))
+            },
+            None => String::new(),
+        }
+    }
 
-        Ok(Page {
-            content: flights,
-            page_number,
-            page_size,
-            total_elements: total_count,
-            total_pages,
+    /// In-memory equivalent of `departure_sql_conditions`
+    pub fn matches_departure(&self, departure: NaiveDate) -> bool {
+        self.weekdays
+            .as_ref()
+            .is_none_or(|weekdays| weekdays.contains(&departure.weekday()))
+    }
+
+    /// In-memory equivalent of `sql_conditions`
+    pub fn matches(&self, flight: &impl CabinAwards, cabin_type: Option<CabinType>) -> bool {
+        if !self.has_cabin_filters() {
+            return true;
+        }
+
+        self.cabins(cabin_type).iter().any(|cabin_type| {
+            let seats = flight.cabin_class_seat_count(*cabin_type);
+            let points = flight.cabin_points_value(*cabin_type);
+            self.min_seats.is_none_or(|min_seats| seats.is_some_and(|seats| seats >= min_seats))
+                && (!self.saver_only || flight.is_saver_award(*cabin_type) == Some(true))
+                && self.min_points.is_none_or(|min_points| points.is_some_and(|points| points >= min_points))
+                && self.max_points.is_none_or(|max_points| points.is_some_and(|points| points <= max_points))
         })
     }
-    
-    async fn find_all_ordered_by_lowest_cabin_points_and_origin_and_destination(
-        &self,
-        origin: &str,
-        destination: &str,
-        cabin_type: &str,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
-        // Calculate offset
-        let offset = (page_number * page_size) as i64;
-        
-        // Get total count
-        let count_query = format!(
-            "SELECT COUNT(*) as count 
-            FROM reward_flights_latest rfl
-            LEFT JOIN award_economy ae ON ae.flight_id = rfl.id
-            LEFT JOIN award_business ab ON ab.flight_id = rfl.id
-            LEFT JOIN award_premium_economy ape ON ape.flight_id = rfl.id
-            WHERE rfl.origin = $1 
-            AND rfl.destination = $2 
-            AND (
-                ($3 = 'ECONOMY' AND ae.cabin_points_value IS NOT NULL AND ae.cabin_class_seat_count > 0) OR
-                ($3 = 'PREMIUM_ECONOMY' AND ape.cabin_points_value IS NOT NULL AND ape.cabin_class_seat_count > 0) OR
-                ($3 = 'BUSINESS' AND ab.cabin_points_value IS NOT NULL AND ab.cabin_class_seat_count > 0)
-            )"
-        );
-        
-        info!("Executing cheapest count SQL query: {}", &count_query);
-        info!("Count query parameters: origin={}, destination={}, cabin_type={}", 
-            origin, destination, cabin_type);
-            
-        let count_result = sqlx::query_as::<_, (i64,)>(&count_query)
-            .bind(origin)
-            .bind(destination)
-            .bind(cabin_type)
-            .fetch_one(&self.pool)
-            .await;
-            
-        info!("Raw Cheapest Count SQL Response: {:?}", count_result);
-        
-        let total_count: i64 = count_result
-            .map(|row| row.0)
-            .unwrap_or(0);
-            
-        info!("Cheapest Count SQL Response: Total count = {}", total_count);
+}
 
-        // Get paginated results
-        let query = format!(
-            "SELECT 
-                rfl.id, 
-                rfl.origin, 
-                rfl.destination, 
-                rfl.departure, 
-                rfl.carrier_code, 
-                rfl.scraped_at,
-                ae.id as ae_id,
-                ae.cabin_points_value as ae_cabin_points_value,
-                ae.is_saver_award as ae_is_saver_award,
-                ae.cabin_class_seat_count as ae_cabin_class_seat_count,
-                ae.cabin_class_seat_count_string as ae_cabin_class_seat_count_string,
-                ab.id as ab_id,
-                ab.cabin_points_value as ab_cabin_points_value,
-                ab.is_saver_award as ab_is_saver_award,
-                ab.cabin_class_seat_count as ab_cabin_class_seat_count,
-                ab.cabin_class_seat_count_string as ab_cabin_class_seat_count_string,
-                ape.id as ape_id,
-                ape.cabin_points_value as ape_cabin_points_value,
-                ape.is_saver_award as ape_is_saver_award,
-                ape.cabin_class_seat_count as ape_cabin_class_seat_count,
-                ape.cabin_class_seat_count_string as ape_cabin_class_seat_count_string,
-                af.id as af_id,
-                af.cabin_points_value as af_cabin_points_value,
-                af.is_saver_award as af_is_saver_award,
-                af.cabin_class_seat_count as af_cabin_class_seat_count,
-                af.cabin_class_seat_count_string as af_cabin_class_seat_count_string
-            FROM reward_flights_latest rfl
-            LEFT JOIN award_economy ae ON ae.flight_id = rfl.id
-            LEFT JOIN award_business ab ON ab.flight_id = rfl.id
-            LEFT JOIN award_premium_economy ape ON ape.flight_id = rfl.id
-            LEFT JOIN award_first af ON af.flight_id = rfl.id
-            WHERE rfl.origin = $1 
-            AND rfl.destination = $2 
-            AND (
-                ($3 = 'ECONOMY' AND ae.cabin_points_value IS NOT NULL AND ae.cabin_class_seat_count > 0) OR
-                ($3 = 'PREMIUM_ECONOMY' AND ape.cabin_points_value IS NOT NULL AND ape.cabin_class_seat_count > 0) OR
-                ($3 = 'BUSINESS' AND ab.cabin_points_value IS NOT NULL AND ab.cabin_class_seat_count > 0)
-            )
-            ORDER BY 
-                CASE 
-                    WHEN $3 = 'ECONOMY' THEN ae.cabin_points_value 
-                    WHEN $3 = 'PREMIUM_ECONOMY' THEN ape.cabin_points_value 
-                    WHEN $3 = 'BUSINESS' THEN ab.cabin_points_value 
-                END ASC,
-                rfl.departure ASC
-            LIMIT $4 OFFSET $5"
-        );
-        
-        info!("Executing cheapest SQL query: {}", &query);
-        info!("Query parameters: origin={}, destination={}, cabin_type={}, limit={}, offset={}", 
-            origin, destination, cabin_type, page_size, offset);
-            
-        let rows = sqlx::query(&query)
-            .bind(origin)
-            .bind(destination)
-            .bind(cabin_type)
-            .bind(page_size as i64)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-            
-        info!("Cheapest SQL Response: Found {} rows", rows.len());
-        info!("Raw Cheapest SQL Response: {:?}", rows);
-        
-        // Convert rows to RewardFlightLatest objects (reusing the same mapping logic)
-        let flights = rows
-            .into_iter()
-            .map(|row| {
-                let award_economy = match row.try_get::<i32, _>("ae_id") {
-                    Ok(id) => {
-                        Some(AwardEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ae_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ae_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ae_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ae_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_business = match row.try_get::<i32, _>("ab_id") {
-                    Ok(id) => {
-                        Some(AwardBusiness {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ab_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ab_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ab_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ab_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_premium_economy = match row.try_get::<i32, _>("ape_id") {
-                    Ok(id) => {
-                        Some(AwardPremiumEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ape_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ape_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ape_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ape_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_first = match row.try_get::<i32, _>("af_id") {
-                    Ok(id) => {
-                        Some(AwardFirst {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("af_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("af_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("af_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("af_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
+// Scope of a bulk export of the latest reward flights; unset fields match every flight
+#[derive(Debug, Clone, Default)]
+pub struct ExportScope {
+    pub origins: Option<Vec<String>>,
+    pub destinations: Option<Vec<String>>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
 
-                let departure: Option<NaiveDate> = row.try_get("departure").ok().flatten();
-                let formatted_departure = departure.map_or_else(
-                    || String::new(), 
-                    |date| date.format("%Y-%m-%d").to_string()
-                );
-                
-                let id = match row.try_get::<i32, _>("id") {
-                    Ok(id) => Some(id.to_string()),
-                    Err(_) => None
-                };
-                
-                RewardFlightLatest {
-                    id,
-                    origin: row.try_get("origin").unwrap_or_default(),
-                    destination: row.try_get("destination").unwrap_or_default(),
-                    departure: formatted_departure,
-                    carrier_code: row.try_get("carrier_code").unwrap_or_default(),
-                    scraped_at: row.try_get("scraped_at").unwrap_or_else(|_| Utc::now()),
-                    award_economy,
-                    award_business,
-                    award_premium_economy,
-                    award_first,
-                }
+// The best flight of one day in a flexible date search
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FlexibleDateOption {
+    pub date: NaiveDate,
+    pub days_from_target: i64,
+    pub cabin_points_value: i32,
+    pub flights_available: usize,
+    pub flight: RewardFlightLatest,
+}
+
+// Flexible date search around a target date, one option per day with availability
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FlexibleDateSearch {
+    pub target_date: NaiveDate,
+    pub flex_days: u32,
+    pub cabin_type: CabinType,
+    pub options: Vec<FlexibleDateOption>,
+}
+
+impl FlexibleDateSearch {
+    /// Groups flights by departure day, keeping the cheapest bookable flight of each day,
+    /// ordered by points then by proximity to the target date
+    pub fn from_flights(target_date: NaiveDate, flex_days: u32, cabin_type: CabinType, flights: Vec<RewardFlightLatest>) -> Self {
+        let mut by_day: HashMap<NaiveDate, FlexibleDateOption> = HashMap::new();
+        for flight in flights {
+            let (Ok(date), Some(points)) = (flight.departure.parse::<NaiveDate>(), flight.cabin_points_value(cabin_type)) else {
+                continue;
+            };
+            if flight.cabin_class_seat_count(cabin_type).unwrap_or(0) <= 0 {
+                continue;
+            }
+            let option = by_day.entry(date).or_insert_with(|| FlexibleDateOption {
+                date,
+                days_from_target: (date - target_date).num_days(),
+                cabin_points_value: points,
+                flights_available: 0,
+                flight: flight.clone(),
+            });
+            option.flights_available += 1;
+            if points < option.cabin_points_value {
+                option.cabin_points_value = points;
+                option.flight = flight;
+            }
+        }
+
+        Self::new(target_date, flex_days, cabin_type, by_day.into_values().collect())
+    }
+
+    /// The first and last departure days within `flex_days` of the target date, if both are valid dates
+    pub fn window(target_date: NaiveDate, flex_days: u32) -> Option<(NaiveDate, NaiveDate)> {
+        let days = chrono::Days::new(flex_days as u64);
+        Some((target_date.checked_sub_days(days)?, target_date.checked_add_days(days)?))
+    }
+
+    /// Orders the options of each day by points then by proximity to the target date
+    pub fn new(target_date: NaiveDate, flex_days: u32, cabin_type: CabinType, mut options: Vec<FlexibleDateOption>) -> Self {
+        options.sort_by(|a, b| {
+            a.cabin_points_value
+                .cmp(&b.cabin_points_value)
+                .then_with(|| a.days_from_target.abs().cmp(&b.days_from_target.abs()))
+                .then_with(|| a.date.cmp(&b.date))
+        });
+
+        Self {
+            target_date,
+            flex_days,
+            cabin_type,
+            options,
+        }
+    }
+}
+
+// How a cabin's current price compares to the route's recent price history
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DealScore {
+    // Percentage of historic prices below this one; 0 is the cheapest seen
+    pub percentile_rank: Option<f64>,
+    pub lowest_seen: bool,
+    pub lowest_points: Option<i32>,
+    pub sample_size: i64,
+    pub lookback_days: u32,
+}
+
+impl DealScore {
+    pub fn new(points: i32, sample_size: i64, lower_count: i64, lowest_points: Option<i32>, lookback_days: u32) -> Self {
+        Self {
+            percentile_rank: (sample_size > 0).then(|| lower_count as f64 * 100.0 / sample_size as f64),
+            lowest_seen: lowest_points.is_none_or(|lowest_points| points <= lowest_points),
+            lowest_points,
+            sample_size,
+            lookback_days,
+        }
+    }
+}
+
+// Deal scores of a flight's cabins; a cabin is absent when the flight has no price for it
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct DealScores {
+    pub economy: Option<DealScore>,
+    pub premium_economy: Option<DealScore>,
+    pub business: Option<DealScore>,
+    pub first: Option<DealScore>,
+}
+
+impl DealScores {
+    pub fn cabin_mut(&mut self, cabin_type: CabinType) -> &mut Option<DealScore> {
+        match cabin_type {
+            CabinType::Economy => &mut self.economy,
+            CabinType::PremiumEconomy => &mut self.premium_economy,
+            CabinType::Business => &mut self.business,
+            CabinType::First => &mut self.first,
+        }
+    }
+}
+
+// Best availability of a cabin on a calendar day
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CalendarCabinAvailability {
+    pub lowest_points: Option<i32>,
+    pub max_seats: Option<i32>,
+    pub saver_available: bool,
+}
+
+// One day of a route's availability calendar; a cabin is absent when it has no award seats
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub economy: Option<CalendarCabinAvailability>,
+    pub premium_economy: Option<CalendarCabinAvailability>,
+    pub business: Option<CalendarCabinAvailability>,
+    pub first: Option<CalendarCabinAvailability>,
+}
+
+impl CalendarDay {
+    pub fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            economy: None,
+            premium_economy: None,
+            business: None,
+            first: None,
+        }
+    }
+
+    pub fn cabin_mut(&mut self, cabin_type: CabinType) -> &mut Option<CalendarCabinAvailability> {
+        match cabin_type {
+            CabinType::Economy => &mut self.economy,
+            CabinType::PremiumEconomy => &mut self.premium_economy,
+            CabinType::Business => &mut self.business,
+            CabinType::First => &mut self.first,
+        }
+    }
+}
+
+// Availability calendar of a route for one month
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RewardFlightCalendar {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub month: String,
+    pub days: Vec<CalendarDay>,
+}
+
+// Coverage of a carrier, origin or destination in the latest scraped data
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AvailabilitySummary {
+    pub code: String,
+    pub name: Option<String>,
+    pub flight_count: i64,
+    pub latest_scraped_at: Option<DateTime<Utc>>,
+    pub earliest_departure: Option<NaiveDate>,
+    pub latest_departure: Option<NaiveDate>,
+}
+
+// A cabin's award availability at one point of a flight's price history
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PriceHistoryPoint {
+    pub scraped_at: DateTime<Utc>,
+    pub points: Option<i32>,
+    pub seats: Option<i32>,
+    pub saver: Option<bool>,
+}
+
+// Price history of one cabin, oldest point first
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct CabinPriceSeries {
+    pub cabin_type: CabinType,
+    pub series: Vec<PriceHistoryPoint>,
+}
+
+// Per-cabin price history of a route on one departure date
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PriceHistory {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub departure: NaiveDate,
+    pub interval: HistoryInterval,
+    pub cabins: Vec<CabinPriceSeries>,
+}
+
+// A change in a cabin's award availability between two consecutive scrapes of a flight
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AvailabilityChange {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub departure: NaiveDate,
+    pub cabin_type: CabinType,
+    pub event_type: ChangeEventType,
+    pub scraped_at: DateTime<Utc>,
+    pub previous_scraped_at: DateTime<Utc>,
+    pub points: Option<i32>,
+    pub previous_points: Option<i32>,
+    pub seats: Option<i32>,
+    pub previous_seats: Option<i32>,
+    pub saver: Option<bool>,
+    pub previous_saver: Option<bool>,
+}
+
+// A flight departure whose latest availability was scraped: its route, carrier and departure date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrapedDeparture {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub departure: NaiveDate,
+}
+
+// Distribution of a route's award prices and availability in one cabin over a lookback window
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct RouteStatistics {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub cabin_type: CabinType,
+    pub lookback_days: u32,
+    pub snapshot_count: i64,
+    pub departure_count: i64,
+    pub min_points: Option<i32>,
+    pub median_points: Option<i32>,
+    pub p90_points: Option<i32>,
+    pub max_points: Option<i32>,
+    pub saver_departure_share: Option<f64>,
+    pub average_seats: Option<f64>,
+}
+
+// Number of saver releases or withdrawals observed at a number of days before departure
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReleaseDayCount {
+    pub days_before: i32,
+    pub event_count: i64,
+}
+
+// When saver seats typically appear or disappear, in days before departure
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReleaseTiming {
+    pub event_count: i64,
+    pub min_days_before: Option<i32>,
+    pub median_days_before: Option<i32>,
+    pub max_days_before: Option<i32>,
+    pub days_before: Vec<ReleaseDayCount>,
+}
+
+impl ReleaseTiming {
+    /// Summarises a histogram of observations ordered by `days_before` ascending
+    pub fn from_histogram(days_before: Vec<ReleaseDayCount>) -> Self {
+        let event_count: i64 = days_before.iter().map(|day| day.event_count).sum();
+        // Nearest-rank median over the expanded observations
+        let median_rank = (event_count + 1) / 2;
+        let mut seen = 0;
+        let median_days_before = days_before
+            .iter()
+            .find(|day| {
+                seen += day.event_count;
+                seen >= median_rank
             })
-            .collect();
+            .map(|day| day.days_before);
 
-        // Calculate total pages
-        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+        Self {
+            event_count,
+            min_days_before: days_before.first().map(|day| day.days_before),
+            median_days_before,
+            max_days_before: days_before.last().map(|day| day.days_before),
+            days_before,
+        }
+    }
+}
 
-        Ok(Page {
-            content: flights,
-            page_number,
-            page_size,
-            total_elements: total_count,
-            total_pages,
-        })
+// Saver release pattern of a route's cabin over a lookback window of scrapes
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReleasePattern {
+    pub origin: String,
+    pub destination: String,
+    pub carrier_code: String,
+    pub cabin_type: CabinType,
+    pub lookback_days: u32,
+    pub released: ReleaseTiming,
+    pub withdrawn: ReleaseTiming,
+}
+
+// Which availability changes to return from the change feed
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFilter {
+    pub cabin_type: Option<CabinType>,
+    pub event_types: Option<Vec<ChangeEventType>>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl ChangeFilter {
+    /// The cabins to detect changes in: the filter's cabin, otherwise every cabin
+    fn cabins(&self) -> Vec<CabinType> {
+        match self.cabin_type {
+            Some(cabin_type) => vec![cabin_type],
+            None => CabinType::ALL.to_vec(),
+        }
     }
-    
-    async fn find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
-        &self,
-        origin: &str,
-        destination: &str,
-        carrier_code: &str,
-        departure_date: NaiveDate,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error> {
-        // Calculate offset
-        let offset = (page_number * page_size) as i64;
-        
-        // Get total count
-        let count_query = format!(
-            "SELECT COUNT(*) as count 
-            FROM reward_flights_history rfh
-            WHERE rfh.origin = $1 
-            AND rfh.destination = $2 
-            AND rfh.carrier_code = $3 
-            AND rfh.departure::date = $4"
-        );
-        
-        info!("Executing historic count SQL query: {}", &count_query);
-        info!("Count query parameters: origin={}, destination={}, carrier_code={}, departure_date={}", 
-            origin, destination, carrier_code, departure_date);
-            
-        let count_result = sqlx::query_as::<_, (i64,)>(&count_query)
-            .bind(origin)
-            .bind(destination)
-            .bind(carrier_code)
-            .bind(departure_date)
-            .fetch_one(&self.pool)
-            .await;
-            
-        info!("Raw Historic Count SQL Response: {:?}", count_result);
-        
-        let total_count: i64 = count_result
-            .map(|row| row.0)
-            .unwrap_or(0);
-            
-        info!("Historic Count SQL Response: Total count = {}", total_count);
 
-        // Get paginated results
-        let query = format!(
-            "SELECT 
-                rfh.id, 
-                rfh.origin, 
-                rfh.destination, 
-                rfh.departure, 
-                rfh.carrier_code, 
-                rfh.scraped_at,
-                ae.id as ae_id,
-                ae.cabin_points_value as ae_cabin_points_value,
-                ae.is_saver_award as ae_is_saver_award,
-                ae.cabin_class_seat_count as ae_cabin_class_seat_count,
-                ae.cabin_class_seat_count_string as ae_cabin_class_seat_count_string,
-                ab.id as ab_id,
-                ab.cabin_points_value as ab_cabin_points_value,
-                ab.is_saver_award as ab_is_saver_award,
-                ab.cabin_class_seat_count as ab_cabin_class_seat_count,
-                ab.cabin_class_seat_count_string as ab_cabin_class_seat_count_string,
-                ape.id as ape_id,
-                ape.cabin_points_value as ape_cabin_points_value,
-                ape.is_saver_award as ape_is_saver_award,
-                ape.cabin_class_seat_count as ape_cabin_class_seat_count,
-                ape.cabin_class_seat_count_string as ape_cabin_class_seat_count_string,
-                af.id as af_id,
-                af.cabin_points_value as af_cabin_points_value,
-                af.is_saver_award as af_is_saver_award,
-                af.cabin_class_seat_count as af_cabin_class_seat_count,
-                af.cabin_class_seat_count_string as af_cabin_class_seat_count_string
-            FROM reward_flights_history rfh
-            LEFT JOIN award_economy_history ae ON ae.flight_id = rfh.id
-            LEFT JOIN award_business_history ab ON ab.flight_id = rfh.id
-            LEFT JOIN award_premium_economy_history ape ON ape.flight_id = rfh.id
-            LEFT JOIN award_first_history af ON af.flight_id = rfh.id
-            WHERE rfh.origin = $1 
-            AND rfh.destination = $2 
-            AND rfh.carrier_code = $3 
-            AND rfh.departure::date = $4
-            ORDER BY rfh.scraped_at DESC
-            LIMIT $5 OFFSET $6"
-        );
-        
-        info!("Executing historic SQL query: {}", &query);
-        info!("Query parameters: origin={}, destination={}, carrier_code={}, departure_date={}, limit={}, offset={}", 
-            origin, destination, carrier_code, departure_date, page_size, offset);
-            
-        let rows = sqlx::query(&query)
-            .bind(origin)
-            .bind(destination)
-            .bind(carrier_code)
-            .bind(departure_date)
-            .bind(page_size as i64)
-            .bind(offset)
-            .fetch_all(&self.pool)
-            .await?;
-            
-        info!("Historic SQL Response: Found {} rows", rows.len());
-        
-        // Convert rows to RewardFlightLatestHistoric objects
-        let flights = rows
-            .into_iter()
-            .map(|row| {
-                let award_economy = match row.try_get::<i32, _>("ae_id") {
-                    Ok(id) => {
-                        Some(AwardEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ae_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ae_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ae_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ae_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_business = match row.try_get::<i32, _>("ab_id") {
-                    Ok(id) => {
-                        Some(AwardBusiness {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ab_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ab_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ab_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ab_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_premium_economy = match row.try_get::<i32, _>("ape_id") {
-                    Ok(id) => {
-                        Some(AwardPremiumEconomy {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("ape_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("ape_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("ape_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("ape_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
-                
-                let award_first = match row.try_get::<i32, _>("af_id") {
-                    Ok(id) => {
-                        Some(AwardFirst {
-                            id: Some(id.to_string()),
-                            cabin_points_value: row.try_get::<i32, _>("af_cabin_points_value").ok(),
-                            is_saver_award: row.try_get::<bool, _>("af_is_saver_award").ok(),
-                            cabin_class_seat_count: row.try_get::<i32, _>("af_cabin_class_seat_count").ok(),
-                            cabin_class_seat_count_string: row.try_get::<String, _>("af_cabin_class_seat_count_string").ok(),
-                        })
-                    },
-                    Err(_) => None
-                };
+    /// The event types to detect: the filter's event types, otherwise every event type
+    fn event_types(&self) -> Vec<ChangeEventType> {
+        match &self.event_types {
+            Some(event_types) => event_types.clone(),
+            None => ChangeEventType::ALL.to_vec(),
+        }
+    }
+
+    /// Whether the change is in one of the filtered cabins and event types and recent enough
+    pub fn matches(&self, change: &AvailabilityChange) -> bool {
+        self.cabin_type.is_none_or(|cabin_type| cabin_type == change.cabin_type)
+            && self.event_types.as_ref().is_none_or(|event_types| event_types.contains(&change.event_type))
+            && self.since.is_none_or(|since| change.scraped_at >= since)
+    }
 
-                let departure: Option<NaiveDate> = row.try_get("departure").ok().flatten();
-                let formatted_departure = departure.map_or_else(
-                    || String::new(), 
-                    |date| date.format("%Y-%m-%d").to_string()
-                );
-                
-                let id = match row.try_get::<i32, _>("id") {
-                    Ok(id) => Some(id.to_string()),
-                    Err(_) => None
+    /// The changes between two consecutive snapshots of the same flight that match this filter
+    pub fn changes_between(&self, previous: &RewardFlightLatestHistoric, current: &RewardFlightLatestHistoric) -> Vec<AvailabilityChange> {
+        if self.since.is_some_and(|since| current.scraped_at < since) {
+            return Vec::new();
+        }
+        let Ok(departure) = current.departure.parse::<NaiveDate>() else {
+            return Vec::new();
+        };
+
+        let mut changes = Vec::new();
+        for cabin_type in self.cabins() {
+            for event_type in self.event_types() {
+                let change = AvailabilityChange {
+                    origin: current.origin.clone(),
+                    destination: current.destination.clone(),
+                    carrier_code: current.carrier_code.clone(),
+                    departure,
+                    cabin_type,
+                    event_type,
+                    scraped_at: current.scraped_at,
+                    previous_scraped_at: previous.scraped_at,
+                    points: current.cabin_points_value(cabin_type),
+                    previous_points: previous.cabin_points_value(cabin_type),
+                    seats: current.cabin_class_seat_count(cabin_type),
+                    previous_seats: previous.cabin_class_seat_count(cabin_type),
+                    saver: current.is_saver_award(cabin_type),
+                    previous_saver: previous.is_saver_award(cabin_type),
                 };
-                
-                RewardFlightLatestHistoric {
-                    id,
-                    origin: row.try_get("origin").unwrap_or_default(),
-                    destination: row.try_get("destination").unwrap_or_default(),
-                    departure: formatted_departure,
-                    carrier_code: row.try_get("carrier_code").unwrap_or_default(),
-                    scraped_at: row.try_get("scraped_at").unwrap_or_else(|_| Utc::now()),
-                    award_economy,
-                    award_business,
-                    award_premium_economy,
-                    award_first,
+                if event_type.detected_in(&change) {
+                    changes.push(change);
                 }
-            })
-            .collect();
+            }
+        }
+        changes
+    }
+}
 
-        // Calculate total pages
-        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+// A saved search: a route, departure window and cabin watched for award availability
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SavedSearch {
+    pub id: i64,
+    pub name: String,
+    pub carrier_code: String,
+    pub origin: String,
+    pub destination: String,
+    pub departure_from: NaiveDate,
+    pub departure_to: NaiveDate,
+    pub cabin_type: CabinType,
+    pub min_seats: i32,
+    pub max_points: Option<i32>,
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
 
-        Ok(Page {
-            content: flights,
-            page_number,
-            page_size,
-            total_elements: total_count,
-            total_pages,
+impl SavedSearch {
+    /// The search filter matching flights with enough seats in the cabin within the points limit
+    pub fn filter(&self) -> SearchFilter {
+        SearchFilter {
+            cabin_type: Some(self.cabin_type),
+            min_seats: Some(self.min_seats),
+            max_points: self.max_points,
+            ..SearchFilter::default()
+        }
+    }
+
+    /// The airport pairs searched, expanding metro area codes to their airports
+    pub fn city_pairs(&self, airports: &AirportRegistry) -> Result<Vec<CityPair>, String> {
+        let origins = expand_airport_codes(airports, std::slice::from_ref(&self.origin))?;
+        let destinations = expand_airport_codes(airports, std::slice::from_ref(&self.destination))?;
+        Ok(city_pairs(&origins, &destinations))
+    }
+}
+
+// Request body for creating or replacing a saved search
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SavedSearchRequest {
+    pub name: String,
+    pub carrier_code: String,
+    pub origin: String,
+    pub destination: String,
+    pub departure_from: NaiveDate,
+    pub departure_to: NaiveDate,
+    pub cabin_type: CabinType,
+    pub min_seats: Option<i32>,
+    pub max_points: Option<i32>,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+}
+
+impl SavedSearchRequest {
+    /// Validates the request against the registries, normalising its carrier and airport codes
+    fn validate(self, carriers: &CarrierRegistry, airports: &AirportRegistry, webhooks: &WebhookConfig) -> Result<SavedSearchRequest, FieldError> {
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err(FieldError::new("name", "Invalid name. Expected a non-empty name"));
+        }
+        let carrier = carriers
+            .find(&self.carrier_code)
+            .ok_or_else(|| FieldError::new("carrier_code", format!("Unknown carrier '{}'", self.carrier_code)))?;
+        if !carrier.supports_cabin(self.cabin_type) {
+            return Err(FieldError::new(
+                "cabin_type",
+                format!("Cabin type {} is not supported by {}", self.cabin_type.as_str(), carrier.display_name),
+            ));
+        }
+        // Metro area codes are kept and expanded when the search is evaluated
+        expand_airport_codes(airports, std::slice::from_ref(&self.origin)).map_err(|message| FieldError::new("origin", message))?;
+        expand_airport_codes(airports, std::slice::from_ref(&self.destination)).map_err(|message| FieldError::new("destination", message))?;
+        if self.departure_from > self.departure_to {
+            return Err(FieldError::new("departure_from", "Invalid departure window. Expected departure_from <= departure_to"));
+        }
+        if self.min_seats.is_some_and(|min_seats| min_seats < 1) {
+            return Err(FieldError::new("min_seats", "Invalid min_seats. Expected a positive number of seats"));
+        }
+        if self.max_points.is_some_and(|max_points| max_points < 0) {
+            return Err(FieldError::new("max_points", "Invalid max_points. Expected a positive number of points"));
+        }
+        if let Some(webhook_url) = &self.webhook_url {
+            check_webhook_url(webhook_url, webhooks.allow_private_networks).map_err(|message| FieldError::new("webhook_url", message))?;
+            if self.webhook_secret.as_deref().is_none_or(|secret| secret.is_empty()) {
+                return Err(FieldError::new("webhook_secret", "Invalid webhook_secret. Expected a secret to sign webhook payloads with"));
+            }
+        }
+
+        Ok(SavedSearchRequest {
+            name,
+            carrier_code: carrier.code.clone(),
+            origin: self.origin.to_uppercase(),
+            destination: self.destination.to_uppercase(),
+            min_seats: Some(self.min_seats.unwrap_or(1)),
+            ..self
         })
     }
 }
 
-// Mock implementation for testing
-pub struct MockRewardFlightRepository;
+// A flight, cabin and price combination sent to a saved search's webhook at most once
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct NotifiedPrice {
+    pub origin: String,
+    pub destination: String,
+    pub departure: NaiveDate,
+    pub cabin_type: CabinType,
+    pub points: i32,
+}
 
-#[async_trait]
-impl RewardFlightRepository for MockRewardFlightRepository {
-    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
-        &self,
-        origin: &str,
-        destination: &str,
-        carrier_code: &str,
-        from_date: NaiveDate,
-        to_date: NaiveDate,
-        page_number: usize,
-        page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
-        // Create some mock data
-        let mut flights = Vec::new();
-        
-        // Generate mock flights for each day in the date range
-        let mut current_date = from_date;
-        while current_date <= to_date {
-            let flight = RewardFlightLatest {
-                id: Some(format!("mock-{}-{}-{}", origin, destination, current_date)),
-                origin: origin.to_string(),
-                destination: destination.to_string(),
-                departure: current_date.to_string(),
-                carrier_code: carrier_code.to_string(),
-                scraped_at: Utc::now(),
-                award_economy: Some(AwardEconomy {
-                    id: Some("mock-economy-id".to_string()),
-                    cabin_points_value: Some(10000),
-                    is_saver_award: Some(true),
-                    cabin_class_seat_count: Some(5),
-                    cabin_class_seat_count_string: Some("5".to_string()),
-                }),
-                award_business: Some(AwardBusiness {
-                    id: Some("mock-business-id".to_string()),
-                    cabin_points_value: Some(30000),
-                    is_saver_award: Some(false),
-                    cabin_class_seat_count: Some(2),
-                    cabin_class_seat_count_string: Some("2".to_string()),
-                }),
-                award_premium_economy: Some(AwardPremiumEconomy {
-                    id: Some("mock-premium-economy-id".to_string()),
-                    cabin_points_value: Some(20000),
-                    is_saver_award: Some(true),
-                    cabin_class_seat_count: Some(3),
-                    cabin_class_seat_count_string: Some("3".to_string()),
-                }),
-                award_first: None,
-            };
-            
-            flights.push(flight);
-            current_date = current_date.succ_opt().unwrap_or(current_date);
+// A flight matching a saved search, as sent to its webhook
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WebhookMatch {
+    pub cabin_type: CabinType,
+    pub points: i32,
+    pub seats: Option<i32>,
+    pub saver: Option<bool>,
+    pub flight: RewardFlightLatest,
+}
+
+// JSON body POSTed to a saved search's webhook
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WebhookPayload {
+    pub saved_search_id: i64,
+    pub saved_search_name: String,
+    pub sent_at: DateTime<Utc>,
+    pub matches: Vec<WebhookMatch>,
+}
+
+// Outcome of a webhook delivery after its final attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DeliveryStatus {
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Delivered => "DELIVERED",
+            DeliveryStatus::Failed => "FAILED",
         }
-        
-        // Calculate total elements
-        let total_elements = flights.len() as i64;
-        
-        // Apply pagination
-        let start = page_number * page_size;
-        let end = std::cmp::min(start + page_size, flights.len());
-        let paginated_flights = if start < flights.len() {
-            flights[start..end].to_vec()
-        } else {
-            Vec::new()
-        };
-        
-        // Calculate total pages
-        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
-        
-        Ok(Page {
-            content: paginated_flights,
-            page_number,
-            page_size,
-            total_elements,
-            total_pages,
+    }
+}
+
+// An entry of the webhook delivery log
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub saved_search_id: i64,
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// A webhook delivery that failed every attempt
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WebhookDeadLetter {
+    pub id: i64,
+    pub delivery_id: i64,
+    pub saved_search_id: i64,
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Pagination response wrapper
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    pub content: Vec<T>,
+    pub page_number: usize,
+    pub page_size: usize,
+    pub total_elements: i64,
+    pub total_pages: usize,
+}
+
+/// Offset of the first row of a page, past the end of any result set when it overflows
+fn page_offset(page_number: usize, page_size: usize) -> i64 {
+    page_number.checked_mul(page_size).and_then(|offset| i64::try_from(offset).ok()).unwrap_or(i64::MAX)
+}
+
+// A reward flight flattened to one CSV row, with the points, seats and saver flag of every cabin
+#[derive(Debug, Serialize, Clone)]
+pub struct RewardFlightCsvRow {
+    pub origin: String,
+    pub destination: String,
+    pub departure: String,
+    pub carrier_code: String,
+    pub scraped_at: DateTime<Utc>,
+    pub economy_points: Option<i32>,
+    pub economy_seats: Option<i32>,
+    pub economy_saver: Option<bool>,
+    pub premium_economy_points: Option<i32>,
+    pub premium_economy_seats: Option<i32>,
+    pub premium_economy_saver: Option<bool>,
+    pub business_points: Option<i32>,
+    pub business_seats: Option<i32>,
+    pub business_saver: Option<bool>,
+    pub first_points: Option<i32>,
+    pub first_seats: Option<i32>,
+    pub first_saver: Option<bool>,
+}
+
+impl RewardFlightCsvRow {
+    // Header row, in the order of the fields
+    pub const HEADERS: [&'static str; 17] = [
+        "origin", "destination", "departure", "carrier_code", "scraped_at",
+        "economy_points", "economy_seats", "economy_saver",
+        "premium_economy_points", "premium_economy_seats", "premium_economy_saver",
+        "business_points", "business_seats", "business_saver",
+        "first_points", "first_seats", "first_saver",
+    ];
+
+    /// Flattens a flight, reading the points, seats and saver flag of each cabin with `cabin`
+    fn new(
+        origin: &str,
+        destination: &str,
+        departure: &str,
+        carrier_code: &str,
+        scraped_at: DateTime<Utc>,
+        cabin: impl Fn(CabinType) -> (Option<i32>, Option<i32>, Option<bool>),
+    ) -> Self {
+        let (economy_points, economy_seats, economy_saver) = cabin(CabinType::Economy);
+        let (premium_economy_points, premium_economy_seats, premium_economy_saver) = cabin(CabinType::PremiumEconomy);
+        let (business_points, business_seats, business_saver) = cabin(CabinType::Business);
+        let (first_points, first_seats, first_saver) = cabin(CabinType::First);
+        Self {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            departure: departure.to_string(),
+            carrier_code: carrier_code.to_string(),
+            scraped_at,
+            economy_points,
+            economy_seats,
+            economy_saver,
+            premium_economy_points,
+            premium_economy_seats,
+            premium_economy_saver,
+            business_points,
+            business_seats,
+            business_saver,
+            first_points,
+            first_seats,
+            first_saver,
+        }
+    }
+}
+
+impl From<&RewardFlightLatest> for RewardFlightCsvRow {
+    fn from(flight: &RewardFlightLatest) -> Self {
+        Self::new(&flight.origin, &flight.destination, &flight.departure, &flight.carrier_code, flight.scraped_at, |cabin_type| {
+            (flight.cabin_points_value(cabin_type), flight.cabin_class_seat_count(cabin_type), flight.is_saver_award(cabin_type))
         })
     }
-    
-    async fn find_all_ordered_by_lowest_cabin_points_and_origin_and_destination(
+}
+
+impl From<&RewardFlightLatestHistoric> for RewardFlightCsvRow {
+    fn from(flight: &RewardFlightLatestHistoric) -> Self {
+        Self::new(&flight.origin, &flight.destination, &flight.departure, &flight.carrier_code, flight.scraped_at, |cabin_type| {
+            (flight.cabin_points_value(cabin_type), flight.cabin_class_seat_count(cabin_type), flight.is_saver_award(cabin_type))
+        })
+    }
+}
+
+// RFC 7807 problem details, returned as `application/problem+json` by every error response
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub errors: Vec<FieldError>,
+}
+
+// A request field that failed validation, listed in a problem's errors
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Problem {
+    /// A problem with the given status, typed and titled after it
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        let (problem_type, title) = match status {
+            StatusCode::BAD_REQUEST => ("/problems/invalid-request", "Invalid request"),
+            StatusCode::NOT_FOUND => ("/problems/not-found", "Resource not found"),
+            StatusCode::INTERNAL_SERVER_ERROR => ("/problems/internal-error", "Internal server error"),
+            _ => ("about:blank", status.canonical_reason().unwrap_or("Error")),
+        };
+        Self {
+            problem_type: problem_type.to_string(),
+            title: title.to_string(),
+            status: status.as_u16(),
+            detail: detail.into(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, detail)
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, detail)
+    }
+
+    pub fn internal_server_error(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, detail)
+    }
+
+    /// A 400 problem for a single field that failed validation
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        FieldError::new(field, message).into()
+    }
+}
+
+impl From<FieldError> for Problem {
+    fn from(error: FieldError) -> Self {
+        Self {
+            errors: vec![error.clone()],
+            ..Self::bad_request(error.message)
+        }
+    }
+}
+
+// The problem responses the API's operations may return, as documented in the OpenAPI specification
+#[derive(IntoResponses)]
+pub enum ProblemResponses {
+    #[response(status = 400, description = "Invalid path, query or body parameters", content_type = "application/problem+json")]
+    BadRequest(#[to_schema] Problem),
+    #[response(status = 404, description = "Unknown carrier or resource", content_type = "application/problem+json")]
+    NotFound(#[to_schema] Problem),
+    #[response(status = 500, description = "Database error", content_type = "application/problem+json")]
+    InternalServerError(#[to_schema] Problem),
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.title, self.detail)
+    }
+}
+
+impl ResponseError for Problem {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code())
+            .content_type("application/problem+json")
+            .json(self)
+    }
+}
+
+// Repository trait for RewardFlightLatest
+#[async_trait]
+pub trait RewardFlightRepository: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
+    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
         &self,
         origin: &str,
         destination: &str,
-        cabin_type: &str,
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
         page_number: usize,
         page_size: usize,
-    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
-        // Create some mock data
-        let mut flights = Vec::new();
-        
-        // Generate 10 mock flights with different points values
-        for i in 0..10 {
-            // Create different points values based on index to simulate ordering
-            let economy_points = 10000 + (i * 1000);
-            let premium_economy_points = 20000 + (i * 1500);
-            let business_points = 30000 + (i * 2000);
-            
-            // Create a date for the flight (today + i days)
-            let today = chrono::Local::now().date_naive();
-            let flight_date = today.checked_add_days(chrono::Days::new(i as u64)).unwrap_or(today);
-            
-            let flight = RewardFlightLatest {
-                id: Some(format!("mock-{}-{}-{}", origin, destination, i)),
-                origin: origin.to_string(),
-                destination: destination.to_string(),
-                departure: flight_date.to_string(),
-                carrier_code: "VS".to_string(),
-                scraped_at: Utc::now(),
-                award_economy: Some(AwardEconomy {
-                    id: Some(format!("mock-economy-id-{}", i)),
-                    cabin_points_value: Some(economy_points),
-                    is_saver_award: Some(true),
-                    cabin_class_seat_count: Some(5),
-                    cabin_class_seat_count_string: Some("5".to_string()),
-                }),
-                award_business: Some(AwardBusiness {
-                    id: Some(format!("mock-business-id-{}", i)),
-                    cabin_points_value: Some(business_points),
-                    is_saver_award: Some(false),
-                    cabin_class_seat_count: Some(2),
-                    cabin_class_seat_count_string: Some("2".to_string()),
-                }),
-                award_premium_economy: Some(AwardPremiumEconomy {
-                    id: Some(format!("mock-premium-economy-id-{}", i)),
-                    cabin_points_value: Some(premium_economy_points),
-                    is_saver_award: Some(true),
-                    cabin_class_seat_count: Some(3),
-                    cabin_class_seat_count_string: Some("3".to_string()),
-                }),
-                award_first: None,
-            };
-            
-            flights.push(flight);
-        }
-        
-        // Sort flights based on cabin type
-        flights.sort_by(|a, b| {
-            let a_points = match cabin_type {
-                "ECONOMY" => a.award_economy.as_ref().and_then(|award| award.cabin_points_value),
-                "PREMIUM_ECONOMY" => a.award_premium_economy.as_ref().and_then(|award| award.cabin_points_value),
-                "BUSINESS" => a.award_business.as_ref().and_then(|award| award.cabin_points_value),
-                _ => None,
-            };
-            
-            let b_points = match cabin_type {
-                "ECONOMY" => b.award_economy.as_ref().and_then(|award| award.cabin_points_value),
-                "PREMIUM_ECONOMY" => b.award_premium_economy.as_ref().and_then(|award| award.cabin_points_value),
-                "BUSINESS" => b.award_business.as_ref().and_then(|award| award.cabin_points_value),
-                _ => None,
-            };
-            
-            // Sort by points (ascending) and then by departure date
-            match (a_points, b_points) {
-                (Some(a_val), Some(b_val)) => a_val.cmp(&b_val),
-                (Some(_), None) => std::cmp::Ordering::Less,
-                (None, Some(_)) => std::cmp::Ordering::Greater,
-                (None, None) => a.departure.cmp(&b.departure),
-            }
-        });
-        
-        // Calculate total elements
-        let total_elements = flights.len() as i64;
-        
-        // Apply pagination
-        let start = page_number * page_size;
-        let end = std::cmp::min(start + page_size, flights.len());
-        let paginated_flights = if start < flights.len() {
-            flights[start..end].to_vec()
-        } else {
-            Vec::new()
-        };
-        
-        // Calculate total pages
-        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
-        
-        Ok(Page {
-            content: paginated_flights,
-            page_number,
-            page_size,
-            total_elements,
-            total_pages,
-        })
-    }
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
+    
+    async fn find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
     
+    #[allow(clippy::too_many_arguments)]
     async fn find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
         &self,
         origin: &str,
         destination: &str,
         carrier_code: &str,
         departure_date: NaiveDate,
+        filter: &SearchFilter,
         page_number: usize,
         page_size: usize,
-    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error> {
-        // Create some mock data
-        let mut flights = Vec::new();
-        
-        // Generate 5 mock historic flights for the given date with different scraped_at times
-        for i in 0..5 {
-            // Convert i to i32 for calculations
-            let i_i32 = i as i32;
-            
-            // Create different scraped_at times (each 1 hour apart)
-            let base_time = Utc::now() - chrono::Duration::hours(24 - i);
-            
-            let flight = RewardFlightLatestHistoric {
-                id: Some(format!("mock-historic-{}-{}-{}", origin, destination, i)),
-                origin: origin.to_string(),
-                destination: destination.to_string(),
+    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_cheapest_per_destination_by_origins_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        sort: ExploreSort,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error>;
+
+    /// The cheapest bookable flight of each day within `flex_days` of the target date
+    async fn find_flexible_dates_by_city_pairs_and_carrier_code_and_cabin_type(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        target_date: NaiveDate,
+        flex_days: u32,
+        filter: &SearchFilter,
+    ) -> Result<FlexibleDateSearch, sqlx::Error>;
+
+    async fn find_calendar_by_origins_and_destinations_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        destinations: &[String],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> Result<Vec<CalendarDay>, sqlx::Error>;
+
+    async fn find_price_history_by_origin_and_destination_and_carrier_code_and_departure(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        interval: HistoryInterval,
+    ) -> Result<Vec<CabinPriceSeries>, sqlx::Error>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_changes_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &ChangeFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<AvailabilityChange>, sqlx::Error>;
+
+    async fn find_statistics_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<RouteStatistics, sqlx::Error>;
+
+    async fn find_deal_scores_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        flights: &[RewardFlightLatest],
+        lookback_days: u32,
+    ) -> Result<Vec<DealScores>, sqlx::Error>;
+
+    async fn find_release_pattern_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<ReleasePattern, sqlx::Error>;
+
+    async fn find_latest_scraped_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error>;
+
+    async fn find_changes_scraped_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityChange>, sqlx::Error>;
+
+    /// The distinct departures whose latest availability was scraped in the window
+    async fn find_departures_scraped_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ScrapedDeparture>, sqlx::Error>;
+
+    async fn find_carrier_summaries(&self) -> Result<Vec<AvailabilitySummary>, sqlx::Error>;
+
+    /// Streams the carrier's latest flights within the scope row by row, without buffering them
+    fn stream_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        scope: &ExportScope,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>>;
+
+    /// Streams every flight of the city pairs search, in the order of its pages
+    fn stream_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>>;
+
+    /// Streams every flight of the cheapest city pairs search, in the order of its pages
+    fn stream_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>>;
+
+    /// Streams every snapshot of the historic search, in the order of its pages
+    fn stream_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatestHistoric, sqlx::Error>>;
+
+    async fn find_origin_summaries_by_carrier_code(
+        &self,
+        carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error>;
+
+    async fn find_destination_summaries_by_origins_and_carrier_code(
+        &self,
+        origins: &[String],
+        carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_itineraries_by_city_pairs_and_carrier_code(
+        &self,
+        outbound_pairs: &[CityPair],
+        return_pairs: &[CityPair],
+        carrier_code: &str,
+        search: &RoundTripSearch,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightRoundTrip>, sqlx::Error>;
+}
+
+// Database implementation of the repository
+pub struct RewardFlightLatestRepository {
+    pool: Pool<Postgres>,
+}
+
+impl RewardFlightLatestRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Loads the given flights with their awards, keyed by flight id
+    async fn find_by_ids(&self, ids: &[i32]) -> Result<HashMap<String, RewardFlightLatest>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let query = format!(
+            "SELECT {}
+            FROM reward_flights_latest rfl
+            {}
+            WHERE rfl.id = ANY($1)",
+            reward_flight_columns("rfl"), REWARD_FLIGHT_LATEST_AWARD_JOINS
+        );
+
+        info!("Executing flights by id SQL query: {}", &query);
+        info!("Query parameters: ids={:?}", ids);
+
+        let rows = sqlx::query(&query)
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Flights by id SQL Response: Found {} rows", rows.len());
+
+        Ok(rows
+            .iter()
+            .map(reward_flight_latest_from_row)
+            .filter_map(|flight| flight.id.clone().map(|id| (id, flight)))
+            .collect())
+    }
+}
+
+/// Maps a `reward_flights_latest` row joined with the award tables (using the
+/// `ae_`, `ab_`, `ape_` and `af_` column prefixes) to a `RewardFlightLatest`
+fn reward_flight_latest_from_row(row: &PgRow) -> RewardFlightLatest {
+    // Construct award structs from joined columns
+    // Looking at the raw SQL response, we need to check if the ae_id column exists and has a value
+    let award_economy = match row.try_get::<i32, _>("ae_id") {
+        Ok(id) => {
+            // If we successfully got an ID, create the award struct
+            Some(AwardEconomy {
+                id: Some(id.to_string()),
+                cabin_points_value: row.try_get::<i32, _>("ae_cabin_points_value").ok(),
+                is_saver_award: row.try_get::<bool, _>("ae_is_saver_award").ok(),
+                cabin_class_seat_count: row.try_get::<i32, _>("ae_cabin_class_seat_count").ok(),
+                cabin_class_seat_count_string: row.try_get::<String, _>("ae_cabin_class_seat_count_string").ok(),
+            })
+        },
+        Err(_) => None
+    };
+    
+    let award_business = match row.try_get::<i32, _>("ab_id") {
+        Ok(id) => {
+            Some(AwardBusiness {
+                id: Some(id.to_string()),
+                cabin_points_value: row.try_get::<i32, _>("ab_cabin_points_value").ok(),
+                is_saver_award: row.try_get::<bool, _>("ab_is_saver_award").ok(),
+                cabin_class_seat_count: row.try_get::<i32, _>("ab_cabin_class_seat_count").ok(),
+                cabin_class_seat_count_string: row.try_get::<String, _>("ab_cabin_class_seat_count_string").ok(),
+            })
+        },
+        Err(_) => None
+    };
+    
+    let award_premium_economy = match row.try_get::<i32, _>("ape_id") {
+        Ok(id) => {
+            Some(AwardPremiumEconomy {
+                id: Some(id.to_string()),
+                cabin_points_value: row.try_get::<i32, _>("ape_cabin_points_value").ok(),
+                is_saver_award: row.try_get::<bool, _>("ape_is_saver_award").ok(),
+                cabin_class_seat_count: row.try_get::<i32, _>("ape_cabin_class_seat_count").ok(),
+                cabin_class_seat_count_string: row.try_get::<String, _>("ape_cabin_class_seat_count_string").ok(),
+            })
+        },
+        Err(_) => None
+    };
+    
+    let award_first = match row.try_get::<i32, _>("af_id") {
+        Ok(id) => {
+            Some(AwardFirst {
+                id: Some(id.to_string()),
+                cabin_points_value: row.try_get::<i32, _>("af_cabin_points_value").ok(),
+                is_saver_award: row.try_get::<bool, _>("af_is_saver_award").ok(),
+                cabin_class_seat_count: row.try_get::<i32, _>("af_cabin_class_seat_count").ok(),
+                cabin_class_seat_count_string: row.try_get::<String, _>("af_cabin_class_seat_count_string").ok(),
+            })
+        },
+        Err(_) => None
+    };
+
+    // Get departure date and format it properly
+    let departure: Option<NaiveDate> = row.try_get("departure").ok().flatten();
+    let formatted_departure = departure.map_or_else(
+        String::new, 
+        |date| date.format("%Y-%m-%d").to_string()
+    );
+    
+    // Get the ID directly as i32 and convert to string
+    let id = match row.try_get::<i32, _>("id") {
+        Ok(id) => Some(id.to_string()),
+        Err(_) => None
+    };
+    
+    RewardFlightLatest {
+        id,
+        origin: row.try_get("origin").unwrap_or_default(),
+        destination: row.try_get("destination").unwrap_or_default(),
+        departure: formatted_departure,
+        carrier_code: row.try_get("carrier_code").unwrap_or_default(),
+        scraped_at: row.try_get("scraped_at").unwrap_or_else(|_| Utc::now()),
+        award_economy,
+        award_business,
+        award_premium_economy,
+        award_first,
+        deal_scores: None,
+    }
+}
+
+/// Maps a calendar row with per-cabin `{alias}_lowest_points`, `{alias}_max_seats`
+/// and `{alias}_saver_available` columns to a `CalendarDay`
+fn calendar_day_from_row(row: &PgRow) -> CalendarDay {
+    let cabin = |cabin_type: CabinType| {
+        let alias = cabin_type.award_alias();
+        let lowest_points = row.try_get::<Option<i32>, _>(format!("{}_lowest_points", alias).as_str()).ok().flatten();
+        lowest_points.map(|lowest_points| CalendarCabinAvailability {
+            lowest_points: Some(lowest_points),
+            max_seats: row.try_get::<Option<i32>, _>(format!("{}_max_seats", alias).as_str()).ok().flatten(),
+            saver_available: row.try_get::<Option<bool>, _>(format!("{}_saver_available", alias).as_str()).ok().flatten().unwrap_or(false),
+        })
+    };
+
+    CalendarDay {
+        date: row.try_get("departure_date").unwrap_or_default(),
+        economy: cabin(CabinType::Economy),
+        premium_economy: cabin(CabinType::PremiumEconomy),
+        business: cabin(CabinType::Business),
+        first: cabin(CabinType::First),
+    }
+}
+
+/// Maps a row of the change feed query to an `AvailabilityChange`
+fn availability_change_from_row(row: &PgRow) -> AvailabilityChange {
+    AvailabilityChange {
+        origin: row.try_get("origin").unwrap_or_default(),
+        destination: row.try_get("destination").unwrap_or_default(),
+        carrier_code: row.try_get("carrier_code").unwrap_or_default(),
+        departure: row.try_get("departure").unwrap_or_default(),
+        cabin_type: row.try_get::<String, _>("cabin_type").ok()
+            .and_then(|cabin_type| cabin_type.parse().ok())
+            .unwrap_or(CabinType::Economy),
+        event_type: row.try_get::<String, _>("event_type").ok()
+            .and_then(|event_type| event_type.parse().ok())
+            .unwrap_or(ChangeEventType::PointsChanged),
+        scraped_at: row.try_get("scraped_at").unwrap_or_else(|_| Utc::now()),
+        previous_scraped_at: row.try_get("previous_scraped_at").unwrap_or_else(|_| Utc::now()),
+        points: row.try_get("points").ok().flatten(),
+        previous_points: row.try_get("previous_points").ok().flatten(),
+        seats: row.try_get("seats").ok().flatten(),
+        previous_seats: row.try_get("previous_seats").ok().flatten(),
+        saver: row.try_get("saver").ok().flatten(),
+        previous_saver: row.try_get("previous_saver").ok().flatten(),
+    }
+}
+
+/// The `snapshots` and `events` CTEs of the change feed: every snapshot of the flights matching
+/// `flight_conditions` next to the previous snapshot of the same flight, and the changes between
+/// them that match the filter and `scraped_conditions`
+fn availability_changes_query(flight_conditions: &str, scraped_conditions: &str, filter: &ChangeFilter) -> String {
+    // Each snapshot alongside the previous snapshot of the same flight
+    let snapshot_columns: Vec<String> = CabinType::ALL
+        .iter()
+        .map(|cabin_type| format!(
+            "{alias}.cabin_points_value as {alias}_points,
+            {alias}.cabin_class_seat_count as {alias}_seats,
+            {alias}.is_saver_award as {alias}_saver,
+            LAG({alias}.cabin_points_value) OVER flight as {alias}_previous_points,
+            LAG({alias}.cabin_class_seat_count) OVER flight as {alias}_previous_seats,
+            LAG({alias}.is_saver_award) OVER flight as {alias}_previous_saver",
+            alias = cabin_type.award_alias()
+        ))
+        .collect();
+
+    // One SELECT per requested cabin and event type
+    let mut event_selects = Vec::new();
+    for cabin_type in filter.cabins() {
+        for event_type in filter.event_types() {
+            event_selects.push(format!(
+                "SELECT origin, destination, carrier_code, departure, scraped_at, previous_scraped_at,
+                '{cabin}' as cabin_type, '{event}' as event_type,
+                {alias}_points as points, {alias}_previous_points as previous_points,
+                {alias}_seats as seats, {alias}_previous_seats as previous_seats,
+                {alias}_saver as saver, {alias}_previous_saver as previous_saver
+                FROM snapshots
+                WHERE previous_scraped_at IS NOT NULL
+                AND {scraped}
+                AND {condition}",
+                cabin = cabin_type.as_str(),
+                event = event_type.as_str(),
+                alias = cabin_type.award_alias(),
+                scraped = scraped_conditions,
+                condition = event_type.sql_condition(cabin_type)
+            ));
+        }
+    }
+
+    format!(
+        "WITH snapshots AS (
+            SELECT 
+                rfh.origin,
+                rfh.destination,
+                rfh.carrier_code,
+                rfh.departure::date as departure,
+                rfh.scraped_at,
+                LAG(rfh.scraped_at) OVER flight as previous_scraped_at,
+                {}
+            FROM reward_flights_history rfh
+            {}
+            WHERE {}
+            WINDOW flight AS (PARTITION BY rfh.origin, rfh.destination, rfh.carrier_code, rfh.departure ORDER BY rfh.scraped_at)
+        ), events AS (
+            {}
+        )",
+        snapshot_columns.join(",\n                "),
+        REWARD_FLIGHT_HISTORIC_AWARD_JOINS,
+        flight_conditions,
+        event_selects.join("\n            UNION ALL\n            ")
+    )
+}
+
+/// Maps a summary row with `code`, `flight_count`, `latest_scraped_at`,
+/// `earliest_departure` and `latest_departure` columns to an `AvailabilitySummary`
+fn availability_summary_from_row(row: &PgRow) -> AvailabilitySummary {
+    AvailabilitySummary {
+        code: row.try_get("code").unwrap_or_default(),
+        name: None,
+        flight_count: row.try_get("flight_count").unwrap_or(0),
+        latest_scraped_at: row.try_get("latest_scraped_at").ok(),
+        earliest_departure: row.try_get("earliest_departure").ok(),
+        latest_departure: row.try_get("latest_departure").ok(),
+    }
+}
+
+/// Maps a `reward_flights_history` row joined to the award history tables to a `RewardFlightLatestHistoric`
+fn reward_flight_latest_historic_from_row(row: &PgRow) -> RewardFlightLatestHistoric {
+    // History rows have the same columns as the latest flights
+    let flight = reward_flight_latest_from_row(row);
+    RewardFlightLatestHistoric {
+        id: flight.id,
+        origin: flight.origin,
+        destination: flight.destination,
+        departure: flight.departure,
+        carrier_code: flight.carrier_code,
+        scraped_at: flight.scraped_at,
+        award_economy: flight.award_economy,
+        award_business: flight.award_business,
+        award_premium_economy: flight.award_premium_economy,
+        award_first: flight.award_first,
+    }
+}
+
+// Aggregates selected for an `AvailabilitySummary`, as read by `availability_summary_from_row`
+const AVAILABILITY_SUMMARY_COLUMNS: &str = "COUNT(*) as flight_count,
+                MAX(rfl.scraped_at) as latest_scraped_at,
+                MIN(rfl.departure::date) as earliest_departure,
+                MAX(rfl.departure::date) as latest_departure";
+
+/// Columns selected for a reward flight aliased `alias` and its awards, as read by `reward_flight_latest_from_row`
+fn reward_flight_columns(alias: &str) -> String {
+    format!(
+        "{alias}.id, 
+                {alias}.origin, 
+                {alias}.destination, 
+                {alias}.departure, 
+                {alias}.carrier_code, 
+                {alias}.scraped_at,
+                ae.id as ae_id,
+                ae.cabin_points_value as ae_cabin_points_value,
+                ae.is_saver_award as ae_is_saver_award,
+                ae.cabin_class_seat_count as ae_cabin_class_seat_count,
+                ae.cabin_class_seat_count_string as ae_cabin_class_seat_count_string,
+                ab.id as ab_id,
+                ab.cabin_points_value as ab_cabin_points_value,
+                ab.is_saver_award as ab_is_saver_award,
+                ab.cabin_class_seat_count as ab_cabin_class_seat_count,
+                ab.cabin_class_seat_count_string as ab_cabin_class_seat_count_string,
+                ape.id as ape_id,
+                ape.cabin_points_value as ape_cabin_points_value,
+                ape.is_saver_award as ape_is_saver_award,
+                ape.cabin_class_seat_count as ape_cabin_class_seat_count,
+                ape.cabin_class_seat_count_string as ape_cabin_class_seat_count_string,
+                af.id as af_id,
+                af.cabin_points_value as af_cabin_points_value,
+                af.is_saver_award as af_is_saver_award,
+                af.cabin_class_seat_count as af_cabin_class_seat_count,
+                af.cabin_class_seat_count_string as af_cabin_class_seat_count_string"
+    )
+}
+
+// Joins from `reward_flights_latest rfl` to the award tables, aliased to match `reward_flight_columns`
+const REWARD_FLIGHT_LATEST_AWARD_JOINS: &str = "LEFT JOIN award_economy ae ON ae.flight_id = rfl.id
+            LEFT JOIN award_business ab ON ab.flight_id = rfl.id
+            LEFT JOIN award_premium_economy ape ON ape.flight_id = rfl.id
+            LEFT JOIN award_first af ON af.flight_id = rfl.id";
+
+/// Latest flights on any of the city pairs `UNNEST($1, $2)` of carrier `$3` departing between
+/// `$4` and `$5` that also meet `conditions`, by departure then route
+fn city_pairs_query(conditions: &str) -> String {
+    format!(
+        "SELECT {}
+            FROM reward_flights_latest rfl
+            {}
+            WHERE (rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+            AND rfl.carrier_code = $3 
+            AND rfl.departure::date BETWEEN $4 AND $5{}
+            ORDER BY rfl.departure ASC, rfl.origin ASC, rfl.destination ASC",
+        reward_flight_columns("rfl"), REWARD_FLIGHT_LATEST_AWARD_JOINS, conditions
+    )
+}
+
+/// Flights on any of the city pairs `UNNEST($1, $2)` of carrier `$3` with seats and a price in the cabin
+fn cheapest_conditions(cabin_type: CabinType) -> String {
+    format!(
+        "(rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+            AND rfl.carrier_code = $3 
+            AND {}",
+        cabin_type.availability_condition()
+    )
+}
+
+/// Flights meeting `cheapest_conditions` and `conditions`, by lowest points in the cabin then departure
+fn cheapest_query(cabin_type: CabinType, conditions: &str) -> String {
+    format!(
+        "SELECT {}
+            FROM reward_flights_latest rfl
+            {}
+            WHERE {}{}
+            ORDER BY {} ASC, rfl.departure ASC, rfl.origin ASC, rfl.destination ASC",
+        reward_flight_columns("rfl"), REWARD_FLIGHT_LATEST_AWARD_JOINS, cheapest_conditions(cabin_type), conditions,
+        cabin_type.points_column()
+    )
+}
+
+// Joins of the awards of each cabin to `reward_flights_history rfh`, aliased as for the latest flights
+const REWARD_FLIGHT_HISTORIC_AWARD_JOINS: &str = "LEFT JOIN award_economy_history ae ON ae.flight_id = rfh.id
+            LEFT JOIN award_business_history ab ON ab.flight_id = rfh.id
+            LEFT JOIN award_premium_economy_history ape ON ape.flight_id = rfh.id
+            LEFT JOIN award_first_history af ON af.flight_id = rfh.id";
+
+/// History snapshots of the flight from `$1` to `$2` of carrier `$3` departing on `$4` that also meet
+/// `conditions`, oldest first, as read by `reward_flight_latest_historic_from_row`
+fn historic_query(conditions: &str) -> String {
+    format!(
+        "SELECT {}
+            FROM reward_flights_history rfh
+            {}
+            WHERE rfh.origin = $1 
+            AND rfh.destination = $2 
+            AND rfh.carrier_code = $3 
+            AND rfh.departure::date = $4{}
+            ORDER BY rfh.scraped_at ASC, rfh.id ASC",
+        reward_flight_columns("rfh"), REWARD_FLIGHT_HISTORIC_AWARD_JOINS, conditions
+    )
+}
+
+/// The filter's conditions on the snapshots of a flight departing on `departure_date`, whose
+/// departure days either all match or none do
+fn historic_sql_conditions(filter: &SearchFilter, departure_date: NaiveDate) -> String {
+    let departure = if filter.matches_departure(departure_date) { "" } else { "\n            AND FALSE" };
+    format!("{}{}", filter.sql_conditions(None), departure)
+}
+
+#[async_trait]
+impl RewardFlightRepository for RewardFlightLatestRepository {
+    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // A single route is the city pairs search over one pair
+        self.find_by_city_pairs_and_carrier_code_and_departure_between(
+            &[CityPair::new(origin, destination)],
+            carrier_code,
+            from_date,
+            to_date,
+            filter,
+            page_number,
+            page_size,
+        ).await
+    }
+    
+    async fn find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+        
+        // Filters apply to the searched cabin
+        let conditions = format!(
+            "{}{}",
+            filter.sql_conditions(Some(cabin_type)),
+            filter.departure_sql_conditions()
+        );
+
+        // Get total count
+        let count_query = format!(
+            "SELECT COUNT(*) as count 
+            FROM reward_flights_latest rfl
+            {}
+            WHERE {}{}",
+            REWARD_FLIGHT_LATEST_AWARD_JOINS, cheapest_conditions(cabin_type), conditions
+        );
+        
+        info!("Executing cheapest count SQL query: {}", &count_query);
+        info!("Count query parameters: city_pairs={:?}, carrier_code={}, cabin_type={}, filter={:?}", 
+            city_pairs, carrier_code, cabin_type.as_str(), filter);
+            
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+            
+        info!("Cheapest Count SQL Response: Total count = {}", total_count);
+
+        // Get paginated results
+        let query = format!("{}\n            LIMIT $4 OFFSET $5", cheapest_query(cabin_type, &conditions));
+        
+        info!("Executing cheapest SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, cabin_type={}, filter={:?}, limit={}, offset={}", 
+            city_pairs, carrier_code, cabin_type.as_str(), filter, page_size, offset);
+            
+        let rows = sqlx::query(&query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+            
+        info!("Cheapest SQL Response: Found {} rows", rows.len());
+        info!("Raw Cheapest SQL Response: {:?}", rows);
+        
+        // Convert rows to RewardFlightLatest objects (reusing the same mapping logic)
+        let flights = rows
+            .iter()
+            .map(reward_flight_latest_from_row)
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: flights,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+        
+        let conditions = historic_sql_conditions(filter, departure_date);
+
+        // Get total count
+        let count_query = format!(
+            "SELECT COUNT(*) as count 
+            FROM reward_flights_history rfh
+            {}
+            WHERE rfh.origin = $1 
+            AND rfh.destination = $2 
+            AND rfh.carrier_code = $3 
+            AND rfh.departure::date = $4{}",
+            REWARD_FLIGHT_HISTORIC_AWARD_JOINS, conditions
+        );
+        
+        info!("Executing historic count SQL query: {}", &count_query);
+        info!("Count query parameters: origin={}, destination={}, carrier_code={}, departure_date={}, filter={:?}", 
+            origin, destination, carrier_code, departure_date, filter);
+            
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(origin)
+            .bind(destination)
+            .bind(carrier_code)
+            .bind(departure_date)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+            
+        info!("Historic Count SQL Response: Total count = {}", total_count);
+
+        // Get paginated results
+        let query = format!("{}\n            LIMIT $5 OFFSET $6", historic_query(&conditions));
+        
+        info!("Executing historic SQL query: {}", query);
+        info!("Query parameters: origin={}, destination={}, carrier_code={}, departure_date={}, filter={:?}, limit={}, offset={}", 
+            origin, destination, carrier_code, departure_date, filter, page_size, offset);
+            
+        let rows = sqlx::query(&query)
+            .bind(origin)
+            .bind(destination)
+            .bind(carrier_code)
+            .bind(departure_date)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+            
+        info!("Historic SQL Response: Found {} rows", rows.len());
+        
+        // Convert rows to RewardFlightLatestHistoric objects
+        let flights = rows
+            .iter()
+            .map(reward_flight_latest_historic_from_row)
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: flights,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_cheapest_per_destination_by_origins_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        sort: ExploreSort,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+        let points_column = cabin_type.points_column();
+        let availability_condition = format!(
+            "{}{}{}",
+            cabin_type.availability_condition(),
+            filter.sql_conditions(Some(cabin_type)),
+            filter.departure_sql_conditions()
+        );
+
+        // Get total count, one per destination
+        let count_query = format!(
+            "SELECT COUNT(DISTINCT rfl.destination) as count 
+            FROM reward_flights_latest rfl
+            {}
+            WHERE rfl.origin = ANY($1) 
+            AND rfl.carrier_code = $2 
+            AND rfl.departure::date BETWEEN $3 AND $4
+            AND {}",
+            REWARD_FLIGHT_LATEST_AWARD_JOINS, availability_condition
+        );
+
+        info!("Executing explore count SQL query: {}", &count_query);
+        info!("Count query parameters: origins={:?}, carrier_code={}, cabin_type={}, from_date={}, to_date={}, filter={:?}", 
+            origins, carrier_code, cabin_type.as_str(), from_date, to_date, filter);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(origins)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        info!("Explore Count SQL Response: Total count = {}", total_count);
+
+        // Pick the cheapest flight per destination, then order and paginate those
+        let query = format!(
+            "SELECT * FROM (
+                SELECT DISTINCT ON (rfl.destination)
+                    {},
+                    {} as selected_cabin_points_value
+                FROM reward_flights_latest rfl
+                {}
+                WHERE rfl.origin = ANY($1) 
+                AND rfl.carrier_code = $2 
+                AND rfl.departure::date BETWEEN $3 AND $4
+                AND {}
+                ORDER BY rfl.destination, {} ASC, rfl.departure ASC
+            ) cheapest
+            ORDER BY {}
+            LIMIT $5 OFFSET $6",
+            reward_flight_columns("rfl"), points_column, REWARD_FLIGHT_LATEST_AWARD_JOINS,
+            availability_condition, points_column, sort.order_by_clause()
+        );
+
+        info!("Executing explore SQL query: {}", &query);
+        info!("Query parameters: origins={:?}, carrier_code={}, cabin_type={}, from_date={}, to_date={}, filter={:?}, sort={:?}, limit={}, offset={}", 
+            origins, carrier_code, cabin_type.as_str(), from_date, to_date, filter, sort, page_size, offset);
+
+        let rows = sqlx::query(&query)
+            .bind(origins)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Explore SQL Response: Found {} rows", rows.len());
+
+        let flights = rows
+            .iter()
+            .map(reward_flight_latest_from_row)
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: flights,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+
+        let conditions = format!("{}{}", filter.sql_conditions(None), filter.departure_sql_conditions());
+
+        // Get total count across all city pairs
+        let count_query = format!(
+            "SELECT COUNT(*) as count 
+            FROM reward_flights_latest rfl
+            {}
+            WHERE (rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+            AND rfl.carrier_code = $3 
+            AND rfl.departure::date BETWEEN $4 AND $5{}",
+            REWARD_FLIGHT_LATEST_AWARD_JOINS, conditions
+        );
+
+        info!("Executing city pairs count SQL query: {}", &count_query);
+        info!("Count query parameters: city_pairs={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}", 
+            city_pairs, carrier_code, from_date, to_date, filter);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        info!("City Pairs Count SQL Response: Total count = {}", total_count);
+
+        // Get paginated results for all city pairs in a single query
+        let query = format!("{}\n            LIMIT $6 OFFSET $7", city_pairs_query(&conditions));
+
+        info!("Executing city pairs SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}, limit={}, offset={}", 
+            city_pairs, carrier_code, from_date, to_date, filter, page_size, offset);
+
+        let rows = sqlx::query(&query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("City Pairs SQL Response: Found {} rows", rows.len());
+
+        let flights = rows
+            .iter()
+            .map(reward_flight_latest_from_row)
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: flights,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_itineraries_by_city_pairs_and_carrier_code(
+        &self,
+        outbound_pairs: &[CityPair],
+        return_pairs: &[CityPair],
+        carrier_code: &str,
+        search: &RoundTripSearch,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightRoundTrip>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+
+        let (outbound_origins, outbound_destinations) = city_pair_arrays(outbound_pairs);
+        let (return_origins, return_destinations) = city_pair_arrays(return_pairs);
+
+        // Candidate legs in each direction, with the points of the requested cabin; the
+        // departure day filters apply to the outbound leg
+        let legs = format!(
+            "WITH outbound AS (
+                SELECT rfl.id, rfl.departure::date AS departure_date, {} AS points
+                FROM reward_flights_latest rfl
+                {}
+                WHERE (rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+                AND rfl.carrier_code = $5 
+                AND rfl.departure::date BETWEEN $6 AND $7
+                AND {}{}{}
+            ), inbound AS (
+                SELECT rfl.id, rfl.departure::date AS departure_date, {} AS points
+                FROM reward_flights_latest rfl
+                {}
+                WHERE (rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($3::text[], $4::text[]))
+                AND rfl.carrier_code = $5 
+                AND rfl.departure::date BETWEEN $8 AND $9
+                AND {}{}
+            )",
+            search.outbound_cabin.points_column(), REWARD_FLIGHT_LATEST_AWARD_JOINS,
+            search.outbound_cabin.availability_condition(), filter.sql_conditions(Some(search.outbound_cabin)),
+            filter.departure_sql_conditions(),
+            search.return_cabin.points_column(), REWARD_FLIGHT_LATEST_AWARD_JOINS,
+            search.return_cabin.availability_condition(), filter.sql_conditions(Some(search.return_cabin))
+        );
+
+        // Get total count of pairs
+        let count_query = format!(
+            "{}
+            SELECT COUNT(*) as count
+            FROM outbound o
+            JOIN inbound i ON (i.departure_date - o.departure_date) BETWEEN $10 AND $11",
+            legs
+        );
+
+        info!("Executing itinerary count SQL query: {}", &count_query);
+        info!("Count query parameters: outbound_pairs={:?}, return_pairs={:?}, carrier_code={}, search={:?}, filter={:?}", 
+            outbound_pairs, return_pairs, carrier_code, search, filter);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(&outbound_origins)
+            .bind(&outbound_destinations)
+            .bind(&return_origins)
+            .bind(&return_destinations)
+            .bind(carrier_code)
+            .bind(search.outbound_from)
+            .bind(search.outbound_to)
+            .bind(search.return_from)
+            .bind(search.return_to)
+            .bind(search.min_trip_days)
+            .bind(search.max_trip_days)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        info!("Itinerary Count SQL Response: Total count = {}", total_count);
+
+        // Get the page of pairs ranked by total points
+        let query = format!(
+            "{}
+            SELECT 
+                o.id as outbound_id,
+                i.id as inbound_id,
+                (i.departure_date - o.departure_date) as trip_length_days,
+                (o.points + i.points) as total_points
+            FROM outbound o
+            JOIN inbound i ON (i.departure_date - o.departure_date) BETWEEN $10 AND $11
+            ORDER BY total_points ASC, o.departure_date ASC, i.departure_date ASC
+            LIMIT $12 OFFSET $13",
+            legs
+        );
+
+        info!("Executing itinerary SQL query: {}", &query);
+        info!("Query parameters: outbound_pairs={:?}, return_pairs={:?}, carrier_code={}, search={:?}, filter={:?}, limit={}, offset={}", 
+            outbound_pairs, return_pairs, carrier_code, search, filter, page_size, offset);
+
+        let pairs = sqlx::query_as::<_, (i32, i32, i32, i32)>(&query)
+            .bind(&outbound_origins)
+            .bind(&outbound_destinations)
+            .bind(&return_origins)
+            .bind(&return_destinations)
+            .bind(carrier_code)
+            .bind(search.outbound_from)
+            .bind(search.outbound_to)
+            .bind(search.return_from)
+            .bind(search.return_to)
+            .bind(search.min_trip_days)
+            .bind(search.max_trip_days)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Itinerary SQL Response: Found {} pairs", pairs.len());
+
+        // Load the paired flights and assemble the round trips
+        let ids: Vec<i32> = pairs
+            .iter()
+            .flat_map(|(outbound_id, inbound_id, _, _)| [*outbound_id, *inbound_id])
+            .collect();
+        let flights = self.find_by_ids(&ids).await?;
+
+        let round_trips = pairs
+            .into_iter()
+            .filter_map(|(outbound_id, inbound_id, trip_length_days, total_points)| {
+                Some(RewardFlightRoundTrip {
+                    outbound: flights.get(&outbound_id.to_string())?.clone(),
+                    inbound: flights.get(&inbound_id.to_string())?.clone(),
+                    outbound_cabin: search.outbound_cabin,
+                    return_cabin: search.return_cabin,
+                    trip_length_days,
+                    total_points,
+                })
+            })
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: round_trips,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_flexible_dates_by_city_pairs_and_carrier_code_and_cabin_type(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        target_date: NaiveDate,
+        flex_days: u32,
+        filter: &SearchFilter,
+    ) -> Result<FlexibleDateSearch, sqlx::Error> {
+        let Some((from_date, to_date)) = FlexibleDateSearch::window(target_date, flex_days) else {
+            return Ok(FlexibleDateSearch::new(target_date, flex_days, cabin_type, Vec::new()));
+        };
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+        let points_column = cabin_type.points_column();
+        let availability_condition = format!(
+            "{}{}{}",
+            cabin_type.availability_condition(),
+            filter.sql_conditions(Some(cabin_type)),
+            filter.departure_sql_conditions()
+        );
+
+        // Pick the cheapest bookable flight per day, counting the day's bookable flights before picking
+        let query = format!(
+            "SELECT DISTINCT ON (rfl.departure::date)
+                {},
+                rfl.departure::date as departure_date,
+                COUNT(*) OVER (PARTITION BY rfl.departure::date) as flights_available
+            FROM reward_flights_latest rfl
+            {}
+            WHERE (rfl.origin, rfl.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+            AND rfl.carrier_code = $3 
+            AND rfl.departure::date BETWEEN $4 AND $5
+            AND {}
+            ORDER BY rfl.departure::date, {} ASC, rfl.departure ASC, rfl.origin ASC, rfl.destination ASC",
+            reward_flight_columns("rfl"), REWARD_FLIGHT_LATEST_AWARD_JOINS, availability_condition, points_column
+        );
+
+        info!("Executing flexible dates SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, cabin_type={}, from_date={}, to_date={}, filter={:?}", 
+            city_pairs, carrier_code, cabin_type.as_str(), from_date, to_date, filter);
+
+        let rows = sqlx::query(&query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Flexible Dates SQL Response: Found {} days", rows.len());
+
+        let options = rows
+            .iter()
+            .map(|row| {
+                let flight = reward_flight_latest_from_row(row);
+                let date: NaiveDate = row.try_get("departure_date")?;
+                let flights_available: i64 = row.try_get("flights_available")?;
+                Ok(FlexibleDateOption {
+                    date,
+                    days_from_target: (date - target_date).num_days(),
+                    cabin_points_value: flight.cabin_points_value(cabin_type).unwrap_or_default(),
+                    flights_available: flights_available as usize,
+                    flight,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(FlexibleDateSearch::new(target_date, flex_days, cabin_type, options))
+    }
+    
+    async fn find_calendar_by_origins_and_destinations_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        destinations: &[String],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> Result<Vec<CalendarDay>, sqlx::Error> {
+        // Aggregate each cabin's bookable awards per departure day
+        let cabin_columns: Vec<String> = CabinType::ALL
+            .iter()
+            .map(|cabin_type| {
+                format!(
+                    "MIN({alias}.cabin_points_value) FILTER (WHERE {condition}) as {alias}_lowest_points,
+                    MAX({alias}.cabin_class_seat_count) FILTER (WHERE {condition}) as {alias}_max_seats,
+                    BOOL_OR({alias}.is_saver_award) FILTER (WHERE {condition}) as {alias}_saver_available",
+                    alias = cabin_type.award_alias(),
+                    condition = format!("{}{}", cabin_type.availability_condition(), filter.sql_conditions(Some(*cabin_type)))
+                )
+            })
+            .collect();
+
+        let query = format!(
+            "SELECT 
+                rfl.departure::date as departure_date,
+                {}
+            FROM reward_flights_latest rfl
+            {}
+            WHERE rfl.origin = ANY($1) 
+            AND rfl.destination = ANY($2) 
+            AND rfl.carrier_code = $3 
+            AND rfl.departure::date BETWEEN $4 AND $5{}
+            GROUP BY rfl.departure::date
+            ORDER BY departure_date ASC",
+            cabin_columns.join(",\n                "), REWARD_FLIGHT_LATEST_AWARD_JOINS, filter.departure_sql_conditions()
+        );
+
+        info!("Executing calendar SQL query: {}", &query);
+        info!("Query parameters: origins={:?}, destinations={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}", 
+            origins, destinations, carrier_code, from_date, to_date, filter);
+
+        let rows = sqlx::query(&query)
+            .bind(origins)
+            .bind(destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Calendar SQL Response: Found {} days", rows.len());
+
+        Ok(rows.iter().map(calendar_day_from_row).collect())
+    }
+    
+    async fn find_carrier_summaries(&self) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        let query = format!(
+            "SELECT 
+                rfl.carrier_code as code,
+                {}
+            FROM reward_flights_latest rfl
+            GROUP BY rfl.carrier_code
+            ORDER BY rfl.carrier_code ASC",
+            AVAILABILITY_SUMMARY_COLUMNS
+        );
+
+        info!("Executing carrier summaries SQL query: {}", &query);
+
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Carrier Summaries SQL Response: Found {} rows", rows.len());
+
+        Ok(rows.iter().map(availability_summary_from_row).collect())
+    }
+    
+    async fn find_origin_summaries_by_carrier_code(
+        &self,
+        carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        let query = format!(
+            "SELECT 
+                rfl.origin as code,
+                {}
+            FROM reward_flights_latest rfl
+            WHERE rfl.carrier_code = $1
+            GROUP BY rfl.origin
+            ORDER BY rfl.origin ASC",
+            AVAILABILITY_SUMMARY_COLUMNS
+        );
+
+        info!("Executing origin summaries SQL query: {}", &query);
+        info!("Query parameters: carrier_code={}", carrier_code);
+
+        let rows = sqlx::query(&query)
+            .bind(carrier_code)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Origin Summaries SQL Response: Found {} rows", rows.len());
+
+        Ok(rows.iter().map(availability_summary_from_row).collect())
+    }
+    
+    async fn find_destination_summaries_by_origins_and_carrier_code(
+        &self,
+        origins: &[String],
+        carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        let query = format!(
+            "SELECT 
+                rfl.destination as code,
+                {}
+            FROM reward_flights_latest rfl
+            WHERE rfl.origin = ANY($1)
+            AND rfl.carrier_code = $2
+            GROUP BY rfl.destination
+            ORDER BY rfl.destination ASC",
+            AVAILABILITY_SUMMARY_COLUMNS
+        );
+
+        info!("Executing destination summaries SQL query: {}", &query);
+        info!("Query parameters: origins={:?}, carrier_code={}", origins, carrier_code);
+
+        let rows = sqlx::query(&query)
+            .bind(origins)
+            .bind(carrier_code)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Destination Summaries SQL Response: Found {} rows", rows.len());
+
+        Ok(rows.iter().map(availability_summary_from_row).collect())
+    }
+    
+    async fn find_price_history_by_origin_and_destination_and_carrier_code_and_departure(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        interval: HistoryInterval,
+    ) -> Result<Vec<CabinPriceSeries>, sqlx::Error> {
+        let cabin_columns: Vec<String> = CabinType::ALL
+            .iter()
+            .map(|cabin_type| format!(
+                "MIN({alias}.cabin_points_value) as {alias}_points,
+                MAX({alias}.cabin_class_seat_count) as {alias}_seats,
+                BOOL_OR({alias}.is_saver_award) as {alias}_saver",
+                alias = cabin_type.award_alias()
+            ))
+            .collect();
+
+        let query = format!(
+            "SELECT 
+                {} as bucket,
+                {}
+            FROM reward_flights_history rfh
+            {}
+            WHERE rfh.origin = $1 
+            AND rfh.destination = $2 
+            AND rfh.carrier_code = $3 
+            AND rfh.departure::date = $4
+            GROUP BY bucket
+            ORDER BY bucket ASC",
+            interval.bucket_expression(),
+            cabin_columns.join(",\n                "),
+            REWARD_FLIGHT_HISTORIC_AWARD_JOINS
+        );
+
+        info!("Executing price history SQL query: {}", &query);
+        info!("Query parameters: origin={}, destination={}, carrier_code={}, departure_date={}, interval={:?}", 
+            origin, destination, carrier_code, departure_date, interval);
+
+        let rows = sqlx::query(&query)
+            .bind(origin)
+            .bind(destination)
+            .bind(carrier_code)
+            .bind(departure_date)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Price History SQL Response: Found {} rows", rows.len());
+
+        Ok(CabinType::ALL
+            .iter()
+            .map(|&cabin_type| {
+                let alias = cabin_type.award_alias();
+                let series = rows
+                    .iter()
+                    .filter_map(|row| {
+                        let point = PriceHistoryPoint {
+                            scraped_at: row.try_get("bucket").ok()?,
+                            points: row.try_get(format!("{}_points", alias).as_str()).ok().flatten(),
+                            seats: row.try_get(format!("{}_seats", alias).as_str()).ok().flatten(),
+                            saver: row.try_get(format!("{}_saver", alias).as_str()).ok().flatten(),
+                        };
+                        // Skip snapshots without an award row for the cabin
+                        (point.points.is_some() || point.seats.is_some() || point.saver.is_some()).then_some(point)
+                    })
+                    .collect();
+                CabinPriceSeries { cabin_type, series }
+            })
+            .collect())
+    }
+    
+    async fn find_changes_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &ChangeFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<AvailabilityChange>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+
+        let events = availability_changes_query(
+            "(rfh.origin, rfh.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+                AND rfh.carrier_code = $3 
+                AND rfh.departure::date BETWEEN $4 AND $5",
+            "($6::timestamptz IS NULL OR scraped_at >= $6)",
+            filter,
+        );
+
+        // Get total count
+        let count_query = format!("{} SELECT COUNT(*) as count FROM events", events);
+
+        info!("Executing changes count SQL query: {}", &count_query);
+        info!("Count query parameters: city_pairs={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}", 
+            city_pairs, carrier_code, from_date, to_date, filter);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(&count_query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .bind(filter.since)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        info!("Changes Count SQL Response: Total count = {}", total_count);
+
+        // Get paginated results, newest changes first
+        let query = format!(
+            "{}
+            SELECT * FROM events
+            ORDER BY scraped_at DESC, departure ASC, origin ASC, destination ASC, cabin_type ASC, event_type ASC
+            LIMIT $7 OFFSET $8",
+            events
+        );
+
+        info!("Executing changes SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}, limit={}, offset={}", 
+            city_pairs, carrier_code, from_date, to_date, filter, page_size, offset);
+
+        let rows = sqlx::query(&query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(carrier_code)
+            .bind(from_date)
+            .bind(to_date)
+            .bind(filter.since)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Changes SQL Response: Found {} rows", rows.len());
+
+        let changes = rows
+            .iter()
+            .map(availability_change_from_row)
+            .collect();
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: changes,
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_statistics_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<RouteStatistics, sqlx::Error> {
+        // Points and seats are only counted for snapshots with bookable seats in the cabin, while
+        // departures count every scraped departure so that the saver share is of all of them
+        let query = format!(
+            "SELECT 
+                COUNT(*) FILTER (WHERE {available}) as snapshot_count,
+                COUNT(DISTINCT rfh.departure::date) as departure_count,
+                MIN({points}) FILTER (WHERE {available}) as min_points,
+                PERCENTILE_DISC(0.5) WITHIN GROUP (ORDER BY {points}) FILTER (WHERE {available}) as median_points,
+                PERCENTILE_DISC(0.9) WITHIN GROUP (ORDER BY {points}) FILTER (WHERE {available}) as p90_points,
+                MAX({points}) FILTER (WHERE {available}) as max_points,
+                COUNT(DISTINCT rfh.departure::date) FILTER (WHERE {available} AND {alias}.is_saver_award)::float8
+                    / NULLIF(COUNT(DISTINCT rfh.departure::date), 0) as saver_departure_share,
+                AVG({alias}.cabin_class_seat_count) FILTER (WHERE {available})::float8 as average_seats
+            FROM reward_flights_history rfh
+            LEFT JOIN {table}_history {alias} ON {alias}.flight_id = rfh.id
+            WHERE rfh.origin = $1 
+            AND rfh.destination = $2 
+            AND rfh.carrier_code = $3 
+            AND rfh.scraped_at >= NOW() - make_interval(days => $4)",
+            available = cabin_type.availability_condition(),
+            points = cabin_type.points_column(),
+            alias = cabin_type.award_alias(),
+            table = cabin_type.award_table()
+        );
+
+        info!("Executing statistics SQL query: {}", &query);
+        info!("Query parameters: origin={}, destination={}, carrier_code={}, cabin_type={}, lookback_days={}", 
+            origin, destination, carrier_code, cabin_type.as_str(), lookback_days);
+
+        let row = sqlx::query(&query)
+            .bind(origin)
+            .bind(destination)
+            .bind(carrier_code)
+            .bind(lookback_days as i32)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(RouteStatistics {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            carrier_code: carrier_code.to_string(),
+            cabin_type,
+            lookback_days,
+            snapshot_count: row.try_get("snapshot_count").unwrap_or(0),
+            departure_count: row.try_get("departure_count").unwrap_or(0),
+            min_points: row.try_get("min_points").ok().flatten(),
+            median_points: row.try_get("median_points").ok().flatten(),
+            p90_points: row.try_get("p90_points").ok().flatten(),
+            max_points: row.try_get("max_points").ok().flatten(),
+            saver_departure_share: row.try_get("saver_departure_share").ok().flatten(),
+            average_seats: row.try_get("average_seats").ok().flatten(),
+        })
+    }
+    
+    async fn find_deal_scores_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        flights: &[RewardFlightLatest],
+        lookback_days: u32,
+    ) -> Result<Vec<DealScores>, sqlx::Error> {
+        // The current price of every cabin of every flight
+        let mut origins = Vec::new();
+        let mut destinations = Vec::new();
+        let mut cabins = Vec::new();
+        let mut prices = Vec::new();
+        for flight in flights {
+            for cabin_type in CabinType::ALL {
+                if let Some(points) = flight.cabin_points_value(cabin_type) {
+                    origins.push(flight.origin.clone());
+                    destinations.push(flight.destination.clone());
+                    cabins.push(cabin_type.as_str().to_string());
+                    prices.push(points);
+                }
+            }
+        }
+        if prices.is_empty() {
+            return Ok(vec![DealScores::default(); flights.len()]);
+        }
+
+        // Available historic prices of each cabin on the same routes
+        let history: Vec<String> = CabinType::ALL
+            .iter()
+            .map(|cabin_type| format!(
+                "SELECT rfh.origin, rfh.destination, '{cabin}' as cabin_type, {points} as points
+                FROM reward_flights_history rfh
+                JOIN {table}_history {alias} ON {alias}.flight_id = rfh.id
+                WHERE (rfh.origin, rfh.destination) IN (SELECT * FROM UNNEST($1::text[], $2::text[]))
+                AND rfh.carrier_code = $5
+                AND rfh.scraped_at >= NOW() - make_interval(days => $6)
+                AND {available}",
+                cabin = cabin_type.as_str(),
+                points = cabin_type.points_column(),
+                table = cabin_type.award_table(),
+                alias = cabin_type.award_alias(),
+                available = cabin_type.availability_condition()
+            ))
+            .collect();
+
+        let query = format!(
+            "WITH prices AS (
+                SELECT DISTINCT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::int[]) AS p(origin, destination, cabin_type, points)
+            ), history AS (
+                {}
+            )
+            SELECT 
+                p.origin,
+                p.destination,
+                p.cabin_type,
+                p.points,
+                COUNT(h.points) as sample_size,
+                COUNT(h.points) FILTER (WHERE h.points < p.points) as lower_count,
+                MIN(h.points) as lowest_points
+            FROM prices p
+            LEFT JOIN history h ON h.origin = p.origin AND h.destination = p.destination AND h.cabin_type = p.cabin_type
+            GROUP BY p.origin, p.destination, p.cabin_type, p.points",
+            history.join("\n                UNION ALL\n                ")
+        );
+
+        info!("Executing deal scores SQL query: {}", &query);
+        info!("Query parameters: carrier_code={}, prices={}, lookback_days={}", carrier_code, prices.len(), lookback_days);
+
+        let rows = sqlx::query(&query)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(&cabins)
+            .bind(&prices)
+            .bind(carrier_code)
+            .bind(lookback_days as i32)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Deal Scores SQL Response: Found {} rows", rows.len());
+
+        let mut scores: HashMap<(String, String, String, i32), DealScore> = HashMap::new();
+        for row in rows {
+            let points: i32 = row.try_get("points")?;
+            let score = DealScore::new(
+                points,
+                row.try_get("sample_size")?,
+                row.try_get("lower_count")?,
+                row.try_get("lowest_points")?,
+                lookback_days,
+            );
+            scores.insert((
+                row.try_get("origin")?,
+                row.try_get("destination")?,
+                row.try_get("cabin_type")?,
+                points,
+            ), score);
+        }
+
+        Ok(flights
+            .iter()
+            .map(|flight| {
+                let mut deal_scores = DealScores::default();
+                for cabin_type in CabinType::ALL {
+                    if let Some(points) = flight.cabin_points_value(cabin_type) {
+                        let key = (flight.origin.clone(), flight.destination.clone(), cabin_type.as_str().to_string(), points);
+                        *deal_scores.cabin_mut(cabin_type) = scores.get(&key).cloned();
+                    }
+                }
+                deal_scores
+            })
+            .collect())
+    }
+    
+    async fn find_release_pattern_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<ReleasePattern, sqlx::Error> {
+        // Saver availability of each snapshot next to the previous snapshot of the same flight,
+        // counting the scrapes at which it flipped by days before departure
+        let saver_available = format!(
+            "COALESCE(({} AND {}.is_saver_award), false)",
+            cabin_type.availability_condition(), cabin_type.award_alias()
+        );
+        let query = format!(
+            "WITH snapshots AS (
+                SELECT 
+                    rfh.departure::date as departure,
+                    rfh.scraped_at,
+                    {saver_available} as saver_available,
+                    LAG({saver_available}) OVER flight as previous_saver_available
+                FROM reward_flights_history rfh
+                LEFT JOIN {table}_history {alias} ON {alias}.flight_id = rfh.id
+                WHERE rfh.origin = $1 
+                AND rfh.destination = $2 
+                AND rfh.carrier_code = $3 
+                AND rfh.scraped_at >= NOW() - make_interval(days => $4)
+                WINDOW flight AS (PARTITION BY rfh.departure ORDER BY rfh.scraped_at)
+            )
+            SELECT 
+                saver_available as released,
+                (departure - scraped_at::date) as days_before,
+                COUNT(*) as event_count
+            FROM snapshots
+            WHERE previous_saver_available IS NOT NULL
+            AND saver_available <> previous_saver_available
+            GROUP BY released, days_before
+            ORDER BY released, days_before ASC",
+            saver_available = saver_available,
+            table = cabin_type.award_table(),
+            alias = cabin_type.award_alias()
+        );
+
+        info!("Executing release pattern SQL query: {}", &query);
+        info!("Query parameters: origin={}, destination={}, carrier_code={}, cabin_type={}, lookback_days={}", 
+            origin, destination, carrier_code, cabin_type.as_str(), lookback_days);
+
+        let rows = sqlx::query(&query)
+            .bind(origin)
+            .bind(destination)
+            .bind(carrier_code)
+            .bind(lookback_days as i32)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Release Pattern SQL Response: Found {} rows", rows.len());
+
+        let mut released = Vec::new();
+        let mut withdrawn = Vec::new();
+        for row in rows {
+            let day = ReleaseDayCount {
+                days_before: row.try_get("days_before").unwrap_or(0),
+                event_count: row.try_get("event_count").unwrap_or(0),
+            };
+            if row.try_get("released").unwrap_or(false) {
+                released.push(day);
+            } else {
+                withdrawn.push(day);
+            }
+        }
+
+        Ok(ReleasePattern {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            carrier_code: carrier_code.to_string(),
+            cabin_type,
+            lookback_days,
+            released: ReleaseTiming::from_histogram(released),
+            withdrawn: ReleaseTiming::from_histogram(withdrawn),
+        })
+    }
+    
+    async fn find_latest_scraped_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let query = "SELECT MAX(rfl.scraped_at) FROM reward_flights_latest rfl";
+
+        let row = sqlx::query_as::<_, (Option<DateTime<Utc>>,)>(query)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(row.0)
+    }
+    
+    async fn find_changes_scraped_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityChange>, sqlx::Error> {
+        // Every change of any flight scraped in the window, diffing its snapshots in the window
+        // against each other and the first against the flight's last snapshot before the window
+        let events = availability_changes_query(
+            "(rfh.scraped_at > $1 AND rfh.scraped_at <= $2)
+                OR rfh.id IN (
+                    SELECT previous.id
+                    FROM (
+                        SELECT DISTINCT origin, destination, carrier_code, departure FROM reward_flights_history
+                        WHERE scraped_at > $1 AND scraped_at <= $2
+                    ) scraped
+                    CROSS JOIN LATERAL (
+                        SELECT prev.id FROM reward_flights_history prev
+                        WHERE prev.origin = scraped.origin
+                        AND prev.destination = scraped.destination
+                        AND prev.carrier_code = scraped.carrier_code
+                        AND prev.departure = scraped.departure
+                        AND prev.scraped_at <= $1
+                        ORDER BY prev.scraped_at DESC
+                        LIMIT 1
+                    ) previous
+                )",
+            "scraped_at > $1 AND scraped_at <= $2",
+            &ChangeFilter::default(),
+        );
+        let query = format!(
+            "{}
+            SELECT * FROM events
+            ORDER BY scraped_at ASC, departure ASC, origin ASC, destination ASC, cabin_type ASC, event_type ASC",
+            events
+        );
+
+        info!("Executing changes since SQL query: {}", &query);
+        info!("Query parameters: after={}, until={}", after, until);
+
+        let rows = sqlx::query(&query)
+            .bind(after)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Changes Since SQL Response: Found {} rows", rows.len());
+
+        Ok(rows.iter().map(availability_change_from_row).collect())
+    }
+    
+    async fn find_departures_scraped_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ScrapedDeparture>, sqlx::Error> {
+        let query = "SELECT DISTINCT rfl.origin, rfl.destination, rfl.carrier_code, rfl.departure::date as departure
+            FROM reward_flights_latest rfl
+            WHERE rfl.scraped_at > $1 AND rfl.scraped_at <= $2";
+
+        info!("Executing departures scraped between SQL query: {}", query);
+        info!("Query parameters: after={}, until={}", after, until);
+
+        let rows = sqlx::query(query)
+            .bind(after)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Departures Scraped Between SQL Response: Found {} rows", rows.len());
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| Some(ScrapedDeparture {
+                origin: row.try_get("origin").ok()?,
+                destination: row.try_get("destination").ok()?,
+                carrier_code: row.try_get("carrier_code").ok()?,
+                departure: row.try_get("departure").ok()?,
+            }))
+            .collect())
+    }
+    
+    fn stream_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        scope: &ExportScope,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        let query = format!(
+            "SELECT {}
+            FROM reward_flights_latest rfl
+            {}
+            WHERE rfl.carrier_code = $1
+            AND ($2::text[] IS NULL OR rfl.origin = ANY($2))
+            AND ($3::text[] IS NULL OR rfl.destination = ANY($3))
+            AND ($4::date IS NULL OR rfl.departure::date >= $4)
+            AND ($5::date IS NULL OR rfl.departure::date <= $5)
+            ORDER BY rfl.origin ASC, rfl.destination ASC, rfl.departure ASC",
+            reward_flight_columns("rfl"), REWARD_FLIGHT_LATEST_AWARD_JOINS
+        );
+
+        info!("Executing export SQL query: {}", &query);
+        info!("Query parameters: carrier_code={}, scope={:?}", carrier_code, scope);
+
+        // The stream owns its query and parameters so it can outlive the request handler
+        let pool = self.pool.clone();
+        let carrier_code = carrier_code.to_string();
+        let scope = scope.clone();
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(&query)
+                .bind(&carrier_code)
+                .bind(&scope.origins)
+                .bind(&scope.destinations)
+                .bind(scope.from_date)
+                .bind(scope.to_date)
+                .fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                yield reward_flight_latest_from_row(&row);
+            }
+        })
+    }
+
+    fn stream_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        let conditions = format!("{}{}", filter.sql_conditions(None), filter.departure_sql_conditions());
+        let query = city_pairs_query(&conditions);
+
+        info!("Executing city pairs export SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, from_date={}, to_date={}, filter={:?}", 
+            city_pairs, carrier_code, from_date, to_date, filter);
+
+        let pool = self.pool.clone();
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+        let carrier_code = carrier_code.to_string();
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(&query)
+                .bind(&origins)
+                .bind(&destinations)
+                .bind(&carrier_code)
+                .bind(from_date)
+                .bind(to_date)
+                .fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                yield reward_flight_latest_from_row(&row);
+            }
+        })
+    }
+
+    fn stream_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        let conditions = format!(
+            "{}{}",
+            filter.sql_conditions(Some(cabin_type)),
+            filter.departure_sql_conditions()
+        );
+        let query = cheapest_query(cabin_type, &conditions);
+
+        info!("Executing cheapest export SQL query: {}", &query);
+        info!("Query parameters: city_pairs={:?}, carrier_code={}, cabin_type={}, filter={:?}", 
+            city_pairs, carrier_code, cabin_type.as_str(), filter);
+
+        let pool = self.pool.clone();
+        let (origins, destinations) = city_pair_arrays(city_pairs);
+        let carrier_code = carrier_code.to_string();
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(&query)
+                .bind(&origins)
+                .bind(&destinations)
+                .bind(&carrier_code)
+                .fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                yield reward_flight_latest_from_row(&row);
+            }
+        })
+    }
+
+    fn stream_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatestHistoric, sqlx::Error>> {
+        let query = historic_query(&historic_sql_conditions(filter, departure_date));
+
+        info!("Executing historic export SQL query: {}", &query);
+        info!("Query parameters: origin={}, destination={}, carrier_code={}, departure_date={}, filter={:?}", 
+            origin, destination, carrier_code, departure_date, filter);
+
+        let pool = self.pool.clone();
+        let (origin, destination, carrier_code) = (origin.to_string(), destination.to_string(), carrier_code.to_string());
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(&query)
+                .bind(&origin)
+                .bind(&destination)
+                .bind(&carrier_code)
+                .bind(departure_date)
+                .fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                yield reward_flight_latest_historic_from_row(&row);
+            }
+        })
+    }
+}
+
+// Mock implementation for testing
+pub struct MockRewardFlightRepository;
+
+#[async_trait]
+impl RewardFlightRepository for MockRewardFlightRepository {
+    async fn find_by_origin_and_destination_and_carrier_code_and_departure_between(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Create some mock data
+        let mut flights = Vec::new();
+        
+        // Generate mock flights for each day in the date range
+        let mut current_date = from_date;
+        while current_date <= to_date {
+            let flight = RewardFlightLatest {
+                id: Some(format!("mock-{}-{}-{}", origin, destination, current_date)),
+                origin: origin.to_string(),
+                destination: destination.to_string(),
+                departure: current_date.to_string(),
+                carrier_code: carrier_code.to_string(),
+                scraped_at: Utc::now(),
+                award_economy: Some(AwardEconomy {
+                    id: Some("mock-economy-id".to_string()),
+                    cabin_points_value: Some(10000),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(5),
+                    cabin_class_seat_count_string: Some("5".to_string()),
+                }),
+                award_business: Some(AwardBusiness {
+                    id: Some("mock-business-id".to_string()),
+                    cabin_points_value: Some(30000),
+                    is_saver_award: Some(false),
+                    cabin_class_seat_count: Some(2),
+                    cabin_class_seat_count_string: Some("2".to_string()),
+                }),
+                award_premium_economy: Some(AwardPremiumEconomy {
+                    id: Some("mock-premium-economy-id".to_string()),
+                    cabin_points_value: Some(20000),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(3),
+                    cabin_class_seat_count_string: Some("3".to_string()),
+                }),
+                award_first: None,
+                deal_scores: None,
+            };
+            
+            if filter.matches(&flight, None) && filter.matches_departure(current_date) {
+                flights.push(flight);
+            }
+            current_date = current_date.succ_opt().unwrap_or(current_date);
+        }
+        
+        // Calculate total elements
+        let total_elements = flights.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), flights.len());
+        let paginated_flights = if start < flights.len() {
+            flights[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_flights,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Create some mock data
+        let mut flights = Vec::new();
+        
+        // Generate 10 mock flights with different points values for each city pair
+        for (city_pair, i) in city_pairs.iter().flat_map(|city_pair| (0..10).map(move |i| (city_pair, i))) {
+            let (origin, destination) = (&city_pair.origin, &city_pair.destination);
+            // Create different points values based on index to simulate ordering
+            let economy_points = 10000 + (i * 1000);
+            let premium_economy_points = 20000 + (i * 1500);
+            let business_points = 30000 + (i * 2000);
+            let first_points = 60000 + (i * 3000);
+            
+            // Create a date for the flight (today + i days)
+            let today = chrono::Local::now().date_naive();
+            let flight_date = today.checked_add_days(chrono::Days::new(i as u64)).unwrap_or(today);
+            
+            let flight = RewardFlightLatest {
+                id: Some(format!("mock-{}-{}-{}", origin, destination, i)),
+                origin: origin.to_string(),
+                destination: destination.to_string(),
+                departure: flight_date.to_string(),
+                carrier_code: carrier_code.to_string(),
+                scraped_at: Utc::now(),
+                award_economy: Some(AwardEconomy {
+                    id: Some(format!("mock-economy-id-{}", i)),
+                    cabin_points_value: Some(economy_points),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(5),
+                    cabin_class_seat_count_string: Some("5".to_string()),
+                }),
+                award_business: Some(AwardBusiness {
+                    id: Some(format!("mock-business-id-{}", i)),
+                    cabin_points_value: Some(business_points),
+                    is_saver_award: Some(false),
+                    cabin_class_seat_count: Some(2),
+                    cabin_class_seat_count_string: Some("2".to_string()),
+                }),
+                award_premium_economy: Some(AwardPremiumEconomy {
+                    id: Some(format!("mock-premium-economy-id-{}", i)),
+                    cabin_points_value: Some(premium_economy_points),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(3),
+                    cabin_class_seat_count_string: Some("3".to_string()),
+                }),
+                award_first: Some(AwardFirst {
+                    id: Some(format!("mock-first-id-{}", i)),
+                    cabin_points_value: Some(first_points),
+                    is_saver_award: Some(false),
+                    cabin_class_seat_count: Some(1),
+                    cabin_class_seat_count_string: Some("1".to_string()),
+                }),
+                deal_scores: None,
+            };
+            
+            if filter.matches(&flight, Some(cabin_type)) && filter.matches_departure(flight_date) {
+                flights.push(flight);
+            }
+        }
+        
+        // Sort flights based on cabin type
+        flights.sort_by(|a, b| {
+            let a_points = a.cabin_points_value(cabin_type);
+            
+            let b_points = b.cabin_points_value(cabin_type);
+            
+            // Sort by points (ascending) and then by departure date
+            match (a_points, b_points) {
+                (Some(a_val), Some(b_val)) => a_val.cmp(&b_val),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.departure.cmp(&b.departure),
+            }
+        });
+        
+        // Calculate total elements
+        let total_elements = flights.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), flights.len());
+        let paginated_flights = if start < flights.len() {
+            flights[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_flights,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatestHistoric>, sqlx::Error> {
+        // Create some mock data
+        let mut flights = Vec::new();
+        
+        // Generate 5 mock historic flights for the given date with different scraped_at times
+        for i in 0..5 {
+            // Convert i to i32 for calculations
+            let i_i32 = i as i32;
+            
+            // Create different scraped_at times (each 1 hour apart)
+            let base_time = Utc::now() - chrono::Duration::hours(24 - i);
+            
+            let flight = RewardFlightLatestHistoric {
+                id: Some(format!("mock-historic-{}-{}-{}", origin, destination, i)),
+                origin: origin.to_string(),
+                destination: destination.to_string(),
                 departure: departure_date.to_string(),
                 carrier_code: carrier_code.to_string(),
-                scraped_at: base_time,
+                scraped_at: base_time,
+                award_economy: Some(AwardEconomy {
+                    id: Some(format!("mock-historic-economy-id-{}", i)),
+                    cabin_points_value: Some(10000 + (i_i32 * 500)),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(5 - i_i32),
+                    cabin_class_seat_count_string: Some(format!("{}", 5 - i_i32)),
+                }),
+                award_business: Some(AwardBusiness {
+                    id: Some(format!("mock-historic-business-id-{}", i)),
+                    cabin_points_value: Some(30000 + (i_i32 * 1000)),
+                    is_saver_award: Some(false),
+                    cabin_class_seat_count: Some(2),
+                    cabin_class_seat_count_string: Some("2".to_string()),
+                }),
+                award_premium_economy: Some(AwardPremiumEconomy {
+                    id: Some(format!("mock-historic-premium-economy-id-{}", i)),
+                    cabin_points_value: Some(20000 + (i_i32 * 750)),
+                    is_saver_award: Some(true),
+                    cabin_class_seat_count: Some(3),
+                    cabin_class_seat_count_string: Some("3".to_string()),
+                }),
+                award_first: None,
+            };
+            
+            if filter.matches(&flight, None) && filter.matches_departure(departure_date) {
+                flights.push(flight);
+            }
+        }
+        
+        // Sort flights by scraped_at (ascending)
+        flights.sort_by_key(|flight| flight.scraped_at);
+        
+        // Calculate total elements
+        let total_elements = flights.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), flights.len());
+        let paginated_flights = if start < flights.len() {
+            flights[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_flights,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_cheapest_per_destination_by_origins_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        sort: ExploreSort,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Create one mock flight per destination, spread across the date range
+        let mut flights = Vec::new();
+        let origin = origins.first().map(String::as_str).unwrap_or_default();
+        let days_in_range = (to_date - from_date).num_days().max(0) as u64;
+
+        for (i, destination) in ["JFK", "LAX", "BOS", "MCO", "BGI"].iter().enumerate() {
+            let i_i32 = i as i32;
+            let flight_date = from_date
+                .checked_add_days(chrono::Days::new((i as u64 * 3) % (days_in_range + 1)))
+                .unwrap_or(from_date);
+
+            let flight = RewardFlightLatest {
+                id: Some(format!("mock-explore-{}-{}", origin, destination)),
+                origin: origin.to_string(),
+                destination: destination.to_string(),
+                departure: flight_date.to_string(),
+                carrier_code: carrier_code.to_string(),
+                scraped_at: Utc::now(),
                 award_economy: Some(AwardEconomy {
-                    id: Some(format!("mock-historic-economy-id-{}", i)),
-                    cabin_points_value: Some(10000 + (i_i32 * 500)),
+                    id: Some(format!("mock-explore-economy-id-{}", i)),
+                    cabin_points_value: Some(25000 - (i_i32 * 2000)),
                     is_saver_award: Some(true),
-                    cabin_class_seat_count: Some(5 - i_i32),
-                    cabin_class_seat_count_string: Some(format!("{}", 5 - i_i32)),
+                    cabin_class_seat_count: Some(5),
+                    cabin_class_seat_count_string: Some("5".to_string()),
                 }),
                 award_business: Some(AwardBusiness {
-                    id: Some(format!("mock-historic-business-id-{}", i)),
-                    cabin_points_value: Some(30000 + (i_i32 * 1000)),
+                    id: Some(format!("mock-explore-business-id-{}", i)),
+                    cabin_points_value: Some(70000 - (i_i32 * 4000)),
                     is_saver_award: Some(false),
                     cabin_class_seat_count: Some(2),
                     cabin_class_seat_count_string: Some("2".to_string()),
                 }),
                 award_premium_economy: Some(AwardPremiumEconomy {
-                    id: Some(format!("mock-historic-premium-economy-id-{}", i)),
-                    cabin_points_value: Some(20000 + (i_i32 * 750)),
+                    id: Some(format!("mock-explore-premium-economy-id-{}", i)),
+                    cabin_points_value: Some(45000 - (i_i32 * 3000)),
                     is_saver_award: Some(true),
                     cabin_class_seat_count: Some(3),
                     cabin_class_seat_count_string: Some("3".to_string()),
                 }),
                 award_first: None,
+                deal_scores: None,
+            };
+
+            if flight.cabin_points_value(cabin_type).is_some()
+                && filter.matches(&flight, Some(cabin_type))
+                && filter.matches_departure(flight_date)
+            {
+                flights.push(flight);
+            }
+        }
+
+        // Sort flights by the requested order
+        match sort {
+            ExploreSort::Points => flights.sort_by(|a, b| {
+                a.cabin_points_value(cabin_type)
+                    .cmp(&b.cabin_points_value(cabin_type))
+                    .then_with(|| a.departure.cmp(&b.departure))
+            }),
+            ExploreSort::Departure => flights.sort_by(|a, b| {
+                a.departure
+                    .cmp(&b.departure)
+                    .then_with(|| a.cabin_points_value(cabin_type).cmp(&b.cabin_points_value(cabin_type)))
+            }),
+        }
+
+        // Calculate total elements
+        let total_elements = flights.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), flights.len());
+        let paginated_flights = if start < flights.len() {
+            flights[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_flights,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Reuse the mock date range search for every city pair
+        let mut flights = Vec::new();
+        for pair in city_pairs {
+            flights.extend(self.find_by_origin_and_destination_and_carrier_code_and_departure_between(
+                &pair.origin, &pair.destination, carrier_code, from_date, to_date, filter, 0, usize::MAX / 2,
+            ).await?.content);
+        }
+
+        // Sort flights by departure, then by route
+        flights.sort_by(|a, b| {
+            a.departure
+                .cmp(&b.departure)
+                .then_with(|| a.origin.cmp(&b.origin))
+                .then_with(|| a.destination.cmp(&b.destination))
+        });
+
+        // Calculate total elements
+        let total_elements = flights.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), flights.len());
+        let paginated_flights = if start < flights.len() {
+            flights[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_flights,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_itineraries_by_city_pairs_and_carrier_code(
+        &self,
+        outbound_pairs: &[CityPair],
+        return_pairs: &[CityPair],
+        carrier_code: &str,
+        search: &RoundTripSearch,
+        filter: &SearchFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<RewardFlightRoundTrip>, sqlx::Error> {
+        // Reuse the mock city pairs search for both legs
+        let outbound_flights: Vec<RewardFlightLatest> = self.find_by_city_pairs_and_carrier_code_and_departure_between(
+            outbound_pairs, carrier_code, search.outbound_from, search.outbound_to, &SearchFilter::default(), 0, usize::MAX / 2,
+        ).await?.content
+            .into_iter()
+            .filter(|flight| {
+                filter.matches(flight, Some(search.outbound_cabin))
+                    && flight.departure.parse::<NaiveDate>().is_ok_and(|departure| filter.matches_departure(departure))
+            })
+            .collect();
+        let inbound_flights: Vec<RewardFlightLatest> = self.find_by_city_pairs_and_carrier_code_and_departure_between(
+            return_pairs, carrier_code, search.return_from, search.return_to, &SearchFilter::default(), 0, usize::MAX / 2,
+        ).await?.content
+            .into_iter()
+            .filter(|flight| filter.matches(flight, Some(search.return_cabin)))
+            .collect();
+
+        // Pair every outbound with every return within the trip length
+        let mut round_trips = Vec::new();
+        for outbound in &outbound_flights {
+            for inbound in &inbound_flights {
+                let (Ok(outbound_date), Ok(inbound_date)) = (
+                    outbound.departure.parse::<NaiveDate>(),
+                    inbound.departure.parse::<NaiveDate>(),
+                ) else {
+                    continue;
+                };
+                let trip_length_days = (inbound_date - outbound_date).num_days() as i32;
+                if trip_length_days < search.min_trip_days || trip_length_days > search.max_trip_days {
+                    continue;
+                }
+                if let (Some(outbound_points), Some(inbound_points)) = (
+                    outbound.cabin_points_value(search.outbound_cabin),
+                    inbound.cabin_points_value(search.return_cabin),
+                ) {
+                    round_trips.push(RewardFlightRoundTrip {
+                        outbound: outbound.clone(),
+                        inbound: inbound.clone(),
+                        outbound_cabin: search.outbound_cabin,
+                        return_cabin: search.return_cabin,
+                        trip_length_days,
+                        total_points: outbound_points + inbound_points,
+                    });
+                }
+            }
+        }
+
+        // Sort round trips by total points, then by departure dates
+        round_trips.sort_by(|a, b| {
+            a.total_points
+                .cmp(&b.total_points)
+                .then_with(|| a.outbound.departure.cmp(&b.outbound.departure))
+                .then_with(|| a.inbound.departure.cmp(&b.inbound.departure))
+        });
+
+        // Calculate total elements
+        let total_elements = round_trips.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), round_trips.len());
+        let paginated_round_trips = if start < round_trips.len() {
+            round_trips[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_round_trips,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_flexible_dates_by_city_pairs_and_carrier_code_and_cabin_type(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        target_date: NaiveDate,
+        flex_days: u32,
+        filter: &SearchFilter,
+    ) -> Result<FlexibleDateSearch, sqlx::Error> {
+        // Group the mock city pairs search of the window by day
+        let Some((from_date, to_date)) = FlexibleDateSearch::window(target_date, flex_days) else {
+            return Ok(FlexibleDateSearch::new(target_date, flex_days, cabin_type, Vec::new()));
+        };
+        let flights = self.find_by_city_pairs_and_carrier_code_and_departure_between(
+            city_pairs,
+            carrier_code,
+            from_date,
+            to_date,
+            filter,
+            0,
+            usize::MAX / 2,
+        ).await?.content;
+
+        Ok(FlexibleDateSearch::from_flights(target_date, flex_days, cabin_type, flights))
+    }
+    
+    async fn find_calendar_by_origins_and_destinations_and_carrier_code_and_departure_between(
+        &self,
+        origins: &[String],
+        destinations: &[String],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> Result<Vec<CalendarDay>, sqlx::Error> {
+        // Summarise the mock date range search, which has one flight per day
+        let flights = self.find_by_city_pairs_and_carrier_code_and_departure_between(
+            &city_pairs(origins, destinations), carrier_code, from_date, to_date, &SearchFilter::default(), 0, usize::MAX / 2,
+        ).await?.content;
+
+        let mut days: Vec<CalendarDay> = Vec::new();
+        for flight in flights {
+            let Ok(date) = flight.departure.parse::<NaiveDate>() else {
+                continue;
+            };
+            if !filter.matches_departure(date) {
+                continue;
+            }
+            if days.last().map(|day| day.date) != Some(date) {
+                days.push(CalendarDay::empty(date));
+            }
+            let day = days.last_mut().expect("day was just pushed");
+
+            for cabin_type in CabinType::ALL {
+                let points = flight.cabin_points_value(cabin_type);
+                let seats = flight.cabin_class_seat_count(cabin_type);
+                if points.is_none() || seats.unwrap_or(0) <= 0 || !filter.matches(&flight, Some(cabin_type)) {
+                    continue;
+                }
+                let cell = day.cabin_mut(cabin_type).get_or_insert(CalendarCabinAvailability {
+                    lowest_points: points,
+                    max_seats: seats,
+                    saver_available: false,
+                });
+                cell.lowest_points = cell.lowest_points.min(points);
+                cell.max_seats = cell.max_seats.max(seats);
+                cell.saver_available |= flight.is_saver_award(cabin_type).unwrap_or(false);
+            }
+        }
+
+        Ok(days)
+    }
+    
+    async fn find_carrier_summaries(&self) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        Ok(vec![mock_availability_summary("VS", 1200)])
+    }
+    
+    async fn find_origin_summaries_by_carrier_code(
+        &self,
+        _carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        Ok(vec![
+            mock_availability_summary("LGW", 150),
+            mock_availability_summary("LHR", 900),
+            mock_availability_summary("MAN", 150),
+        ])
+    }
+    
+    async fn find_destination_summaries_by_origins_and_carrier_code(
+        &self,
+        _origins: &[String],
+        _carrier_code: &str,
+    ) -> Result<Vec<AvailabilitySummary>, sqlx::Error> {
+        Ok(vec![
+            mock_availability_summary("BOS", 120),
+            mock_availability_summary("JFK", 360),
+            mock_availability_summary("LAX", 240),
+        ])
+    }
+    
+    async fn find_price_history_by_origin_and_destination_and_carrier_code_and_departure(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        interval: HistoryInterval,
+    ) -> Result<Vec<CabinPriceSeries>, sqlx::Error> {
+        // Bucket the mock historic snapshots the same way the SQL aggregates do
+        let snapshots = self.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+            origin, destination, carrier_code, departure_date, &SearchFilter::default(), 0, usize::MAX / 2,
+        ).await?.content;
+
+        Ok(CabinType::ALL
+            .iter()
+            .map(|&cabin_type| {
+                let mut series: Vec<PriceHistoryPoint> = Vec::new();
+                for snapshot in &snapshots {
+                    let points = snapshot.cabin_points_value(cabin_type);
+                    let seats = snapshot.cabin_class_seat_count(cabin_type);
+                    let saver = snapshot.is_saver_award(cabin_type);
+                    if points.is_none() && seats.is_none() && saver.is_none() {
+                        continue;
+                    }
+                    let bucket = interval.truncate(snapshot.scraped_at);
+                    match series.last_mut() {
+                        Some(point) if point.scraped_at == bucket => {
+                            point.points = point.points.into_iter().chain(points).min();
+                            point.seats = point.seats.max(seats);
+                            point.saver = point.saver.into_iter().chain(saver).reduce(|a, b| a || b);
+                        },
+                        _ => series.push(PriceHistoryPoint { scraped_at: bucket, points, seats, saver }),
+                    }
+                }
+                CabinPriceSeries { cabin_type, series }
+            })
+            .collect())
+    }
+    
+    async fn find_changes_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &ChangeFilter,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<AvailabilityChange>, sqlx::Error> {
+        // Diff the mock historic snapshots of every flight in the range
+        let mut changes = Vec::new();
+        for city_pair in city_pairs {
+            for departure_date in from_date.iter_days().take_while(|date| *date <= to_date) {
+                let snapshots = self.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                    &city_pair.origin, &city_pair.destination, carrier_code, departure_date, &SearchFilter::default(), 0, usize::MAX / 2,
+                ).await?.content;
+                for pair in snapshots.windows(2) {
+                    changes.extend(filter.changes_between(&pair[0], &pair[1]));
+                }
+            }
+        }
+
+        // Newest changes first
+        changes.sort_by(|a, b| b.scraped_at.cmp(&a.scraped_at).then(a.departure.cmp(&b.departure)));
+        
+        // Calculate total elements
+        let total_elements = changes.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), changes.len());
+        let paginated_changes = if start < changes.len() {
+            changes[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_changes,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_statistics_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<RouteStatistics, sqlx::Error> {
+        // Summarise the mock historic snapshots of the next week of departures
+        let today = chrono::Local::now().date_naive();
+        let since = Utc::now() - chrono::Duration::days(lookback_days as i64);
+        let mut snapshots = Vec::new();
+        for departure_date in today.iter_days().take(7) {
+            snapshots.extend(self.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                origin, destination, carrier_code, departure_date, &SearchFilter::default(), 0, usize::MAX / 2,
+            ).await?.content.into_iter().filter(|snapshot| snapshot.scraped_at >= since));
+        }
+
+        let available: Vec<&RewardFlightLatestHistoric> = snapshots
+            .iter()
+            .filter(|snapshot| snapshot.cabin_points_value(cabin_type).is_some() && snapshot.cabin_class_seat_count(cabin_type).unwrap_or(0) > 0)
+            .collect();
+        let mut points: Vec<i32> = available.iter().filter_map(|snapshot| snapshot.cabin_points_value(cabin_type)).collect();
+        points.sort();
+        // Nearest-rank percentile, as PERCENTILE_DISC computes it
+        let percentile = |fraction: f64| {
+            let rank = ((fraction * points.len() as f64).ceil() as usize).max(1);
+            points.get(rank - 1).copied()
+        };
+
+        let departures: std::collections::HashSet<&str> = snapshots.iter().map(|snapshot| snapshot.departure.as_str()).collect();
+        let saver_departures: std::collections::HashSet<&str> = available
+            .iter()
+            .filter(|snapshot| snapshot.is_saver_award(cabin_type).unwrap_or(false))
+            .map(|snapshot| snapshot.departure.as_str())
+            .collect();
+        let seats: Vec<i32> = available.iter().filter_map(|snapshot| snapshot.cabin_class_seat_count(cabin_type)).collect();
+
+        Ok(RouteStatistics {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            carrier_code: carrier_code.to_string(),
+            cabin_type,
+            lookback_days,
+            snapshot_count: available.len() as i64,
+            departure_count: departures.len() as i64,
+            min_points: points.first().copied(),
+            median_points: percentile(0.5),
+            p90_points: percentile(0.9),
+            max_points: points.last().copied(),
+            saver_departure_share: (!departures.is_empty()).then(|| saver_departures.len() as f64 / departures.len() as f64),
+            average_seats: (!seats.is_empty()).then(|| seats.iter().sum::<i32>() as f64 / seats.len() as f64),
+        })
+    }
+    
+    async fn find_deal_scores_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        flights: &[RewardFlightLatest],
+        lookback_days: u32,
+    ) -> Result<Vec<DealScores>, sqlx::Error> {
+        // Rank each price against the mock historic snapshots of the same flight
+        let mut all_scores = Vec::new();
+        for flight in flights {
+            let departure_date = flight.departure.parse::<NaiveDate>().unwrap_or_else(|_| chrono::Local::now().date_naive());
+            let snapshots = self.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                &flight.origin, &flight.destination, carrier_code, departure_date, &SearchFilter::default(), 0, usize::MAX / 2,
+            ).await?.content;
+
+            let mut deal_scores = DealScores::default();
+            for cabin_type in CabinType::ALL {
+                let Some(points) = flight.cabin_points_value(cabin_type) else {
+                    continue;
+                };
+                let history: Vec<i32> = snapshots
+                    .iter()
+                    .filter(|snapshot| snapshot.cabin_class_seat_count(cabin_type).unwrap_or(0) > 0)
+                    .filter_map(|snapshot| snapshot.cabin_points_value(cabin_type))
+                    .collect();
+                *deal_scores.cabin_mut(cabin_type) = Some(DealScore::new(
+                    points,
+                    history.len() as i64,
+                    history.iter().filter(|&&historic| historic < points).count() as i64,
+                    history.iter().min().copied(),
+                    lookback_days,
+                ));
+            }
+            all_scores.push(deal_scores);
+        }
+
+        Ok(all_scores)
+    }
+    
+    async fn find_release_pattern_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        cabin_type: CabinType,
+        lookback_days: u32,
+    ) -> Result<ReleasePattern, sqlx::Error> {
+        // Mock savers released around a year out and a fortnight out, withdrawn in the last weeks
+        let histogram = |days: &[(i32, i64)]| days
+            .iter()
+            .map(|&(days_before, event_count)| ReleaseDayCount { days_before, event_count })
+            .collect();
+
+        Ok(ReleasePattern {
+            origin: origin.to_string(),
+            destination: destination.to_string(),
+            carrier_code: carrier_code.to_string(),
+            cabin_type,
+            lookback_days,
+            released: ReleaseTiming::from_histogram(histogram(&[(13, 4), (14, 9), (15, 3), (330, 12), (331, 5)])),
+            withdrawn: ReleaseTiming::from_histogram(histogram(&[(3, 6), (7, 8), (21, 2)])),
+        })
+    }
+    
+    async fn find_latest_scraped_at(&self) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        Ok(Some(Utc::now()))
+    }
+    
+    async fn find_changes_scraped_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityChange>, sqlx::Error> {
+        // Changes of the mock LHR to JFK flights departing in the next week
+        let today = chrono::Local::now().date_naive();
+        let to_date = today + chrono::Days::new(7);
+        let changes = self.find_changes_by_city_pairs_and_carrier_code_and_departure_between(
+            &[CityPair::new("LHR", "JFK")], "VS", today, to_date, &ChangeFilter::default(), 0, usize::MAX / 2,
+        ).await?.content;
+
+        let mut changes: Vec<AvailabilityChange> = changes
+            .into_iter()
+            .filter(|change| change.scraped_at > after && change.scraped_at <= until)
+            .collect();
+        changes.sort_by_key(|change| change.scraped_at);
+        Ok(changes)
+    }
+    
+    async fn find_departures_scraped_between(
+        &self,
+        _after: DateTime<Utc>,
+        _until: DateTime<Utc>,
+    ) -> Result<Vec<ScrapedDeparture>, sqlx::Error> {
+        // The mock LHR to JFK flights departing in the next week, which are scraped in every window
+        let today = chrono::Local::now().date_naive();
+        Ok((0..7)
+            .map(|days| ScrapedDeparture {
+                origin: "LHR".to_string(),
+                destination: "JFK".to_string(),
+                carrier_code: "VS".to_string(),
+                departure: today + chrono::Days::new(days),
+            })
+            .collect())
+    }
+    
+    fn stream_by_carrier_code(
+        &self,
+        carrier_code: &str,
+        scope: &ExportScope,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        // Reuse the mock city pairs search, defaulting to a week of LHR to JFK
+        let today = chrono::Local::now().date_naive();
+        let origins = scope.origins.clone().unwrap_or_else(|| vec!["LHR".to_string()]);
+        let destinations = scope.destinations.clone().unwrap_or_else(|| vec!["JFK".to_string()]);
+        let from_date = scope.from_date.unwrap_or(today);
+        let to_date = scope.to_date.unwrap_or(today + chrono::Days::new(7));
+        let carrier_code = carrier_code.to_string();
+        Box::pin(async_stream::try_stream! {
+            let flights = MockRewardFlightRepository.find_by_city_pairs_and_carrier_code_and_departure_between(
+                &city_pairs(&origins, &destinations), &carrier_code, from_date, to_date, &SearchFilter::default(), 0, usize::MAX / 2,
+            ).await?.content;
+            for flight in flights {
+                yield flight;
+            }
+        })
+    }
+
+    fn stream_by_city_pairs_and_carrier_code_and_departure_between(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        from_date: NaiveDate,
+        to_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        let (city_pairs, carrier_code, filter) = (city_pairs.to_vec(), carrier_code.to_string(), filter.clone());
+        Box::pin(async_stream::try_stream! {
+            let flights = MockRewardFlightRepository.find_by_city_pairs_and_carrier_code_and_departure_between(
+                &city_pairs, &carrier_code, from_date, to_date, &filter, 0, usize::MAX / 2,
+            ).await?.content;
+            for flight in flights {
+                yield flight;
+            }
+        })
+    }
+
+    fn stream_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &self,
+        city_pairs: &[CityPair],
+        carrier_code: &str,
+        cabin_type: CabinType,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        let (city_pairs, carrier_code, filter) =
+            (city_pairs.to_vec(), carrier_code.to_string(), filter.clone());
+        Box::pin(async_stream::try_stream! {
+            let flights = MockRewardFlightRepository.find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+                &city_pairs, &carrier_code, cabin_type, &filter, 0, usize::MAX / 2,
+            ).await?.content;
+            for flight in flights {
+                yield flight;
+            }
+        })
+    }
+
+    fn stream_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &self,
+        origin: &str,
+        destination: &str,
+        carrier_code: &str,
+        departure_date: NaiveDate,
+        filter: &SearchFilter,
+    ) -> BoxStream<'static, Result<RewardFlightLatestHistoric, sqlx::Error>> {
+        let (origin, destination, carrier_code, filter) =
+            (origin.to_string(), destination.to_string(), carrier_code.to_string(), filter.clone());
+        Box::pin(async_stream::try_stream! {
+            let flights = MockRewardFlightRepository.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                &origin, &destination, &carrier_code, departure_date, &filter, 0, usize::MAX / 2,
+            ).await?.content;
+            for flight in flights {
+                yield flight;
+            }
+        })
+    }
+}
+
+/// A mock summary covering the next year of departures
+fn mock_availability_summary(code: &str, flight_count: i64) -> AvailabilitySummary {
+    let today = chrono::Local::now().date_naive();
+    AvailabilitySummary {
+        code: code.to_string(),
+        name: None,
+        flight_count,
+        latest_scraped_at: Some(Utc::now()),
+        earliest_departure: Some(today),
+        latest_departure: today.checked_add_days(chrono::Days::new(330)),
+    }
+}
+
+// Repository for the saved searches managed by this service
+#[async_trait]
+pub trait WatchlistRepository: Send + Sync {
+    async fn find_all_saved_searches(&self) -> Result<Vec<SavedSearch>, sqlx::Error>;
+
+    async fn find_saved_search_by_id(&self, id: i64) -> Result<Option<SavedSearch>, sqlx::Error>;
+
+    async fn create_saved_search(&self, request: &SavedSearchRequest) -> Result<SavedSearch, sqlx::Error>;
+
+    async fn update_saved_search(&self, id: i64, request: &SavedSearchRequest) -> Result<Option<SavedSearch>, sqlx::Error>;
+
+    async fn delete_saved_search(&self, id: i64) -> Result<bool, sqlx::Error>;
+
+    /// Records the prices as notified, returning only those not notified before
+    async fn claim_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<Vec<NotifiedPrice>, sqlx::Error>;
+
+    /// Forgets prices claimed for a delivery that could not be logged, so that they are sent again
+    async fn release_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<(), sqlx::Error>;
+
+    async fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<WebhookDelivery, sqlx::Error>;
+
+    async fn create_webhook_dead_letter(&self, delivery: &WebhookDelivery) -> Result<WebhookDeadLetter, sqlx::Error>;
+
+    async fn find_webhook_dead_letter_by_id(&self, id: i64) -> Result<Option<WebhookDeadLetter>, sqlx::Error>;
+
+    async fn delete_webhook_dead_letter(&self, id: i64) -> Result<bool, sqlx::Error>;
+
+    async fn find_webhook_deliveries_by_saved_search_id(
+        &self,
+        saved_search_id: i64,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDelivery>, sqlx::Error>;
+
+    async fn find_webhook_dead_letters(
+        &self,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDeadLetter>, sqlx::Error>;
+}
+
+// Database implementation of the watchlist repository, over the `saved_searches` table
+pub struct SavedSearchRepository {
+    pool: Pool<Postgres>,
+}
+
+impl SavedSearchRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+/// Maps a `saved_searches` row to a `SavedSearch`
+fn saved_search_from_row(row: &PgRow) -> SavedSearch {
+    SavedSearch {
+        id: row.try_get("id").unwrap_or_default(),
+        name: row.try_get("name").unwrap_or_default(),
+        carrier_code: row.try_get("carrier_code").unwrap_or_default(),
+        origin: row.try_get("origin").unwrap_or_default(),
+        destination: row.try_get("destination").unwrap_or_default(),
+        departure_from: row.try_get("departure_from").unwrap_or_default(),
+        departure_to: row.try_get("departure_to").unwrap_or_default(),
+        cabin_type: row.try_get::<String, _>("cabin_type").ok()
+            .and_then(|cabin_type| cabin_type.parse().ok())
+            .unwrap_or(CabinType::Economy),
+        min_seats: row.try_get("min_seats").unwrap_or(1),
+        max_points: row.try_get("max_points").ok().flatten(),
+        webhook_url: row.try_get("webhook_url").ok().flatten(),
+        webhook_secret: row.try_get("webhook_secret").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        updated_at: row.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Maps a `webhook_deliveries` row to a `WebhookDelivery`
+fn webhook_delivery_from_row(row: &PgRow) -> WebhookDelivery {
+    WebhookDelivery {
+        id: row.try_get("id").unwrap_or_default(),
+        saved_search_id: row.try_get("saved_search_id").unwrap_or_default(),
+        url: row.try_get("url").unwrap_or_default(),
+        payload: row.try_get("payload").unwrap_or_default(),
+        status: match row.try_get::<String, _>("status").as_deref() {
+            Ok("DELIVERED") => DeliveryStatus::Delivered,
+            _ => DeliveryStatus::Failed,
+        },
+        attempts: row.try_get("attempts").unwrap_or(0),
+        response_status: row.try_get("response_status").ok().flatten(),
+        error: row.try_get("error").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Maps a `webhook_dead_letters` row to a `WebhookDeadLetter`
+fn webhook_dead_letter_from_row(row: &PgRow) -> WebhookDeadLetter {
+    WebhookDeadLetter {
+        id: row.try_get("id").unwrap_or_default(),
+        delivery_id: row.try_get("delivery_id").unwrap_or_default(),
+        saved_search_id: row.try_get("saved_search_id").unwrap_or_default(),
+        url: row.try_get("url").unwrap_or_default(),
+        payload: row.try_get("payload").unwrap_or_default(),
+        error: row.try_get("error").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+#[async_trait]
+impl WatchlistRepository for SavedSearchRepository {
+    async fn find_all_saved_searches(&self) -> Result<Vec<SavedSearch>, sqlx::Error> {
+        let query = "SELECT * FROM saved_searches ORDER BY id ASC";
+
+        info!("Executing saved searches SQL query: {}", query);
+
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Saved Searches SQL Response: Found {} rows", rows.len());
+
+        Ok(rows.iter().map(saved_search_from_row).collect())
+    }
+    
+    async fn find_saved_search_by_id(&self, id: i64) -> Result<Option<SavedSearch>, sqlx::Error> {
+        let query = "SELECT * FROM saved_searches WHERE id = $1";
+
+        info!("Executing saved search SQL query: {}", query);
+        info!("Query parameters: id={}", id);
+
+        let row = sqlx::query(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(saved_search_from_row))
+    }
+    
+    async fn create_saved_search(&self, request: &SavedSearchRequest) -> Result<SavedSearch, sqlx::Error> {
+        let query = "INSERT INTO saved_searches 
+                (name, carrier_code, origin, destination, departure_from, departure_to, cabin_type, min_seats, max_points, webhook_url, webhook_secret)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING *";
+
+        info!("Executing create saved search SQL query: {}", query);
+        info!("Query parameters: name={}, carrier_code={}, origin={}, destination={}", 
+            request.name, request.carrier_code, request.origin, request.destination);
+
+        let row = sqlx::query(query)
+            .bind(&request.name)
+            .bind(&request.carrier_code)
+            .bind(&request.origin)
+            .bind(&request.destination)
+            .bind(request.departure_from)
+            .bind(request.departure_to)
+            .bind(request.cabin_type.as_str())
+            .bind(request.min_seats.unwrap_or(1))
+            .bind(request.max_points)
+            .bind(&request.webhook_url)
+            .bind(&request.webhook_secret)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(saved_search_from_row(&row))
+    }
+    
+    async fn update_saved_search(&self, id: i64, request: &SavedSearchRequest) -> Result<Option<SavedSearch>, sqlx::Error> {
+        let query = "UPDATE saved_searches SET 
+                name = $2,
+                carrier_code = $3,
+                origin = $4,
+                destination = $5,
+                departure_from = $6,
+                departure_to = $7,
+                cabin_type = $8,
+                min_seats = $9,
+                max_points = $10,
+                webhook_url = $11,
+                webhook_secret = $12,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *";
+
+        info!("Executing update saved search SQL query: {}", query);
+        info!("Query parameters: id={}, name={}, carrier_code={}, origin={}, destination={}", 
+            id, request.name, request.carrier_code, request.origin, request.destination);
+
+        let row = sqlx::query(query)
+            .bind(id)
+            .bind(&request.name)
+            .bind(&request.carrier_code)
+            .bind(&request.origin)
+            .bind(&request.destination)
+            .bind(request.departure_from)
+            .bind(request.departure_to)
+            .bind(request.cabin_type.as_str())
+            .bind(request.min_seats.unwrap_or(1))
+            .bind(request.max_points)
+            .bind(&request.webhook_url)
+            .bind(&request.webhook_secret)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(saved_search_from_row))
+    }
+    
+    async fn delete_saved_search(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let query = "DELETE FROM saved_searches WHERE id = $1";
+
+        info!("Executing delete saved search SQL query: {}", query);
+        info!("Query parameters: id={}", id);
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+    
+    async fn claim_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<Vec<NotifiedPrice>, sqlx::Error> {
+        if prices.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let origins: Vec<&str> = prices.iter().map(|price| price.origin.as_str()).collect();
+        let destinations: Vec<&str> = prices.iter().map(|price| price.destination.as_str()).collect();
+        let departures: Vec<NaiveDate> = prices.iter().map(|price| price.departure).collect();
+        let cabins: Vec<&str> = prices.iter().map(|price| price.cabin_type.as_str()).collect();
+        let points: Vec<i32> = prices.iter().map(|price| price.points).collect();
+
+        // Prices notified before conflict and are left out of the returned rows
+        let query = "INSERT INTO webhook_notified_prices (saved_search_id, origin, destination, departure, cabin_type, points)
+            SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::date[], $5::text[], $6::int[])
+            ON CONFLICT DO NOTHING
+            RETURNING origin, destination, departure, cabin_type, points";
+
+        info!("Executing claim notified prices SQL query: {}", query);
+        info!("Query parameters: saved_search_id={}, prices={}", saved_search_id, prices.len());
+
+        let rows = sqlx::query(query)
+            .bind(saved_search_id)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(&departures)
+            .bind(&cabins)
+            .bind(&points)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Claim Notified Prices SQL Response: Claimed {} rows", rows.len());
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| Some(NotifiedPrice {
+                origin: row.try_get("origin").ok()?,
+                destination: row.try_get("destination").ok()?,
+                departure: row.try_get("departure").ok()?,
+                cabin_type: row.try_get::<String, _>("cabin_type").ok()?.parse().ok()?,
+                points: row.try_get("points").ok()?,
+            }))
+            .collect())
+    }
+    
+    async fn release_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<(), sqlx::Error> {
+        if prices.is_empty() {
+            return Ok(());
+        }
+
+        let origins: Vec<&str> = prices.iter().map(|price| price.origin.as_str()).collect();
+        let destinations: Vec<&str> = prices.iter().map(|price| price.destination.as_str()).collect();
+        let departures: Vec<NaiveDate> = prices.iter().map(|price| price.departure).collect();
+        let cabins: Vec<&str> = prices.iter().map(|price| price.cabin_type.as_str()).collect();
+        let points: Vec<i32> = prices.iter().map(|price| price.points).collect();
+
+        let query = "DELETE FROM webhook_notified_prices
+            WHERE saved_search_id = $1
+            AND (origin, destination, departure, cabin_type, points) IN
+                (SELECT * FROM UNNEST($2::text[], $3::text[], $4::date[], $5::text[], $6::int[]))";
+
+        info!("Executing release notified prices SQL query: {}", query);
+        info!("Query parameters: saved_search_id={}, prices={}", saved_search_id, prices.len());
+
+        let result = sqlx::query(query)
+            .bind(saved_search_id)
+            .bind(&origins)
+            .bind(&destinations)
+            .bind(&departures)
+            .bind(&cabins)
+            .bind(&points)
+            .execute(&self.pool)
+            .await?;
+
+        info!("Release Notified Prices SQL Response: Released {} rows", result.rows_affected());
+
+        Ok(())
+    }
+    
+    async fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<WebhookDelivery, sqlx::Error> {
+        let query = "INSERT INTO webhook_deliveries 
+                (saved_search_id, url, payload, status, attempts, response_status, error)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *";
+
+        info!("Executing create webhook delivery SQL query: {}", query);
+        info!("Query parameters: saved_search_id={}, url={}, status={}, attempts={}", 
+            delivery.saved_search_id, delivery.url, delivery.status.as_str(), delivery.attempts);
+
+        let row = sqlx::query(query)
+            .bind(delivery.saved_search_id)
+            .bind(&delivery.url)
+            .bind(&delivery.payload)
+            .bind(delivery.status.as_str())
+            .bind(delivery.attempts)
+            .bind(delivery.response_status)
+            .bind(&delivery.error)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(webhook_delivery_from_row(&row))
+    }
+    
+    async fn create_webhook_dead_letter(&self, delivery: &WebhookDelivery) -> Result<WebhookDeadLetter, sqlx::Error> {
+        let query = "INSERT INTO webhook_dead_letters 
+                (delivery_id, saved_search_id, url, payload, error)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *";
+
+        info!("Executing create webhook dead letter SQL query: {}", query);
+        info!("Query parameters: delivery_id={}, saved_search_id={}", delivery.id, delivery.saved_search_id);
+
+        let row = sqlx::query(query)
+            .bind(delivery.id)
+            .bind(delivery.saved_search_id)
+            .bind(&delivery.url)
+            .bind(&delivery.payload)
+            .bind(&delivery.error)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(webhook_dead_letter_from_row(&row))
+    }
+    
+    async fn find_webhook_dead_letter_by_id(&self, id: i64) -> Result<Option<WebhookDeadLetter>, sqlx::Error> {
+        let query = "SELECT * FROM webhook_dead_letters WHERE id = $1";
+
+        info!("Executing find webhook dead letter SQL query: {}", query);
+        info!("Query parameters: id={}", id);
+
+        let row = sqlx::query(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(webhook_dead_letter_from_row))
+    }
+    
+    async fn delete_webhook_dead_letter(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let query = "DELETE FROM webhook_dead_letters WHERE id = $1";
+
+        info!("Executing delete webhook dead letter SQL query: {}", query);
+        info!("Query parameters: id={}", id);
+
+        let result = sqlx::query(query)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+    
+    async fn find_webhook_deliveries_by_saved_search_id(
+        &self,
+        saved_search_id: i64,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDelivery>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+
+        let count_query = "SELECT COUNT(*) as count FROM webhook_deliveries WHERE saved_search_id = $1";
+
+        info!("Executing webhook deliveries count SQL query: {}", count_query);
+        info!("Count query parameters: saved_search_id={}", saved_search_id);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(count_query)
+            .bind(saved_search_id)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        let query = "SELECT * FROM webhook_deliveries 
+            WHERE saved_search_id = $1
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2 OFFSET $3";
+
+        info!("Executing webhook deliveries SQL query: {}", query);
+        info!("Query parameters: saved_search_id={}, limit={}, offset={}", saved_search_id, page_size, offset);
+
+        let rows = sqlx::query(query)
+            .bind(saved_search_id)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Webhook Deliveries SQL Response: Found {} rows", rows.len());
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: rows.iter().map(webhook_delivery_from_row).collect(),
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+    
+    async fn find_webhook_dead_letters(
+        &self,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDeadLetter>, sqlx::Error> {
+        // Calculate offset
+        let offset = page_offset(page_number, page_size);
+
+        let count_query = "SELECT COUNT(*) as count FROM webhook_dead_letters";
+
+        info!("Executing webhook dead letters count SQL query: {}", count_query);
+
+        let total_count: i64 = sqlx::query_as::<_, (i64,)>(count_query)
+            .fetch_one(&self.pool)
+            .await?
+            .0;
+
+        let query = "SELECT * FROM webhook_dead_letters 
+            ORDER BY created_at DESC, id DESC
+            LIMIT $1 OFFSET $2";
+
+        info!("Executing webhook dead letters SQL query: {}", query);
+        info!("Query parameters: limit={}, offset={}", page_size, offset);
+
+        let rows = sqlx::query(query)
+            .bind(page_size as i64)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+        info!("Webhook Dead Letters SQL Response: Found {} rows", rows.len());
+
+        // Calculate total pages
+        let total_pages = (total_count as f64 / page_size as f64).ceil() as usize;
+
+        Ok(Page {
+            content: rows.iter().map(webhook_dead_letter_from_row).collect(),
+            page_number,
+            page_size,
+            total_elements: total_count,
+            total_pages,
+        })
+    }
+}
+
+// Mock implementation for testing, keeping saved searches in memory
+#[derive(Default)]
+pub struct MockWatchlistRepository {
+    saved_searches: std::sync::Mutex<Vec<SavedSearch>>,
+    notified_prices: std::sync::Mutex<std::collections::HashSet<(i64, NotifiedPrice)>>,
+    deliveries: std::sync::Mutex<Vec<WebhookDelivery>>,
+    dead_letters: std::sync::Mutex<Vec<WebhookDeadLetter>>,
+}
+
+#[async_trait]
+impl WatchlistRepository for MockWatchlistRepository {
+    async fn find_all_saved_searches(&self) -> Result<Vec<SavedSearch>, sqlx::Error> {
+        Ok(self.saved_searches.lock().expect("mock saved searches lock").clone())
+    }
+    
+    async fn find_saved_search_by_id(&self, id: i64) -> Result<Option<SavedSearch>, sqlx::Error> {
+        let saved_searches = self.saved_searches.lock().expect("mock saved searches lock");
+        Ok(saved_searches.iter().find(|saved_search| saved_search.id == id).cloned())
+    }
+    
+    async fn create_saved_search(&self, request: &SavedSearchRequest) -> Result<SavedSearch, sqlx::Error> {
+        let mut saved_searches = self.saved_searches.lock().expect("mock saved searches lock");
+        let saved_search = SavedSearch {
+            id: saved_searches.iter().map(|saved_search| saved_search.id).max().unwrap_or(0) + 1,
+            name: request.name.clone(),
+            carrier_code: request.carrier_code.clone(),
+            origin: request.origin.clone(),
+            destination: request.destination.clone(),
+            departure_from: request.departure_from,
+            departure_to: request.departure_to,
+            cabin_type: request.cabin_type,
+            min_seats: request.min_seats.unwrap_or(1),
+            max_points: request.max_points,
+            webhook_url: request.webhook_url.clone(),
+            webhook_secret: request.webhook_secret.clone(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        saved_searches.push(saved_search.clone());
+        Ok(saved_search)
+    }
+    
+    async fn update_saved_search(&self, id: i64, request: &SavedSearchRequest) -> Result<Option<SavedSearch>, sqlx::Error> {
+        let mut saved_searches = self.saved_searches.lock().expect("mock saved searches lock");
+        let Some(saved_search) = saved_searches.iter_mut().find(|saved_search| saved_search.id == id) else {
+            return Ok(None);
+        };
+        *saved_search = SavedSearch {
+            name: request.name.clone(),
+            carrier_code: request.carrier_code.clone(),
+            origin: request.origin.clone(),
+            destination: request.destination.clone(),
+            departure_from: request.departure_from,
+            departure_to: request.departure_to,
+            cabin_type: request.cabin_type,
+            min_seats: request.min_seats.unwrap_or(1),
+            max_points: request.max_points,
+            webhook_url: request.webhook_url.clone(),
+            webhook_secret: request.webhook_secret.clone(),
+            updated_at: Utc::now(),
+            ..saved_search.clone()
+        };
+        Ok(Some(saved_search.clone()))
+    }
+    
+    async fn delete_saved_search(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let mut saved_searches = self.saved_searches.lock().expect("mock saved searches lock");
+        let count = saved_searches.len();
+        saved_searches.retain(|saved_search| saved_search.id != id);
+        Ok(saved_searches.len() < count)
+    }
+    
+    async fn claim_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<Vec<NotifiedPrice>, sqlx::Error> {
+        let mut notified_prices = self.notified_prices.lock().expect("mock notified prices lock");
+        Ok(prices
+            .iter()
+            .filter(|price| notified_prices.insert((saved_search_id, (*price).clone())))
+            .cloned()
+            .collect())
+    }
+    
+    async fn release_notified_prices(&self, saved_search_id: i64, prices: &[NotifiedPrice]) -> Result<(), sqlx::Error> {
+        let mut notified_prices = self.notified_prices.lock().expect("mock notified prices lock");
+        for price in prices {
+            notified_prices.remove(&(saved_search_id, price.clone()));
+        }
+        Ok(())
+    }
+    
+    async fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<WebhookDelivery, sqlx::Error> {
+        let mut deliveries = self.deliveries.lock().expect("mock deliveries lock");
+        let delivery = WebhookDelivery {
+            id: deliveries.len() as i64 + 1,
+            created_at: Utc::now(),
+            ..delivery.clone()
+        };
+        deliveries.push(delivery.clone());
+        Ok(delivery)
+    }
+    
+    async fn create_webhook_dead_letter(&self, delivery: &WebhookDelivery) -> Result<WebhookDeadLetter, sqlx::Error> {
+        let mut dead_letters = self.dead_letters.lock().expect("mock dead letters lock");
+        let dead_letter = WebhookDeadLetter {
+            id: dead_letters.iter().map(|dead_letter| dead_letter.id).max().unwrap_or(0) + 1,
+            delivery_id: delivery.id,
+            saved_search_id: delivery.saved_search_id,
+            url: delivery.url.clone(),
+            payload: delivery.payload.clone(),
+            error: delivery.error.clone(),
+            created_at: Utc::now(),
+        };
+        dead_letters.push(dead_letter.clone());
+        Ok(dead_letter)
+    }
+    
+    async fn find_webhook_dead_letter_by_id(&self, id: i64) -> Result<Option<WebhookDeadLetter>, sqlx::Error> {
+        let dead_letters = self.dead_letters.lock().expect("mock dead letters lock");
+        Ok(dead_letters.iter().find(|dead_letter| dead_letter.id == id).cloned())
+    }
+    
+    async fn delete_webhook_dead_letter(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let mut dead_letters = self.dead_letters.lock().expect("mock dead letters lock");
+        let count = dead_letters.len();
+        dead_letters.retain(|dead_letter| dead_letter.id != id);
+        Ok(dead_letters.len() < count)
+    }
+    
+    async fn find_webhook_deliveries_by_saved_search_id(
+        &self,
+        saved_search_id: i64,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDelivery>, sqlx::Error> {
+        // Newest deliveries first
+        let deliveries: Vec<WebhookDelivery> = self.deliveries.lock().expect("mock deliveries lock")
+            .iter()
+            .rev()
+            .filter(|delivery| delivery.saved_search_id == saved_search_id)
+            .cloned()
+            .collect();
+        
+        // Calculate total elements
+        let total_elements = deliveries.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), deliveries.len());
+        let paginated_deliveries = if start < deliveries.len() {
+            deliveries[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_deliveries,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+    
+    async fn find_webhook_dead_letters(
+        &self,
+        page_number: usize,
+        page_size: usize,
+    ) -> Result<Page<WebhookDeadLetter>, sqlx::Error> {
+        // Newest dead letters first
+        let dead_letters: Vec<WebhookDeadLetter> = self.dead_letters.lock().expect("mock dead letters lock")
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+        
+        // Calculate total elements
+        let total_elements = dead_letters.len() as i64;
+        
+        // Apply pagination
+        let start = page_number.saturating_mul(page_size);
+        let end = std::cmp::min(start.saturating_add(page_size), dead_letters.len());
+        let paginated_dead_letters = if start < dead_letters.len() {
+            dead_letters[start..end].to_vec()
+        } else {
+            Vec::new()
+        };
+        
+        // Calculate total pages
+        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
+        
+        Ok(Page {
+            content: paginated_dead_letters,
+            page_number,
+            page_size,
+            total_elements,
+            total_pages,
+        })
+    }
+}
+
+// Retry and polling settings of webhook delivery
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    pub poll_interval: std::time::Duration,
+    pub max_attempts: u32,
+    pub initial_backoff: std::time::Duration,
+    pub request_timeout: std::time::Duration,
+    pub concurrency: usize,
+    pub allow_private_networks: bool,
+}
+
+impl WebhookConfig {
+    /// Reads `WEBHOOK_POLL_SECONDS`, `WEBHOOK_MAX_ATTEMPTS`, `WEBHOOK_INITIAL_BACKOFF_MS`,
+    /// `WEBHOOK_TIMEOUT_SECONDS`, `WEBHOOK_CONCURRENCY` and `WEBHOOK_ALLOW_PRIVATE_NETWORKS`,
+    /// falling back to the defaults
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            std::env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            poll_interval: std::time::Duration::from_secs(env_u64("WEBHOOK_POLL_SECONDS", 300)),
+            max_attempts: env_u64("WEBHOOK_MAX_ATTEMPTS", 5).max(1) as u32,
+            initial_backoff: std::time::Duration::from_millis(env_u64("WEBHOOK_INITIAL_BACKOFF_MS", 1000)),
+            request_timeout: std::time::Duration::from_secs(env_u64("WEBHOOK_TIMEOUT_SECONDS", 10)),
+            concurrency: env_u64("WEBHOOK_CONCURRENCY", 8).max(1) as usize,
+            // Only meant for local development against webhook stubs
+            allow_private_networks: std::env::var("WEBHOOK_ALLOW_PRIVATE_NETWORKS").is_ok_and(|value| value == "true"),
+        }
+    }
+
+    /// The wait before the given retry, doubling from `initial_backoff`
+    fn backoff(&self, retry: u32) -> std::time::Duration {
+        self.initial_backoff.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+/// Whether an address is publicly routable, rather than loopback, private, link-local
+/// (including cloud metadata endpoints), shared, multicast or otherwise reserved
+fn is_public_address(ip: std::net::IpAddr) -> bool {
+    match ip {
+        std::net::IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                || (first == 100 && second & 0xc0 == 64)
+                || first >= 240)
+        },
+        std::net::IpAddr::V6(ip) => match embedded_ipv4_address(ip) {
+            Some(ip) => is_public_address(std::net::IpAddr::V4(ip)),
+            None => !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()),
+        },
+    }
+}
+
+/// The IPv4 address an IPv6 address reaches: the one embedded in IPv4-mapped (`::ffff:0:0/96`),
+/// IPv4-compatible (`::/96`), NAT64 (`64:ff9b::/96` and `64:ff9b:1::/48`) and 6to4 (`2002::/16`) addresses
+fn embedded_ipv4_address(ip: std::net::Ipv6Addr) -> Option<std::net::Ipv4Addr> {
+    let octets = ip.octets();
+    let ipv4 = |offset: usize| std::net::Ipv4Addr::new(octets[offset], octets[offset + 1], octets[offset + 2], octets[offset + 3]);
+    match ip.segments() {
+        [0, 0, 0, 0, 0, 0xffff, _, _] | [0, 0, 0, 0, 0, 0, _, _] | [0x64, 0xff9b, 0, 0, 0, 0, _, _] | [0x64, 0xff9b, 1, ..] => Some(ipv4(12)),
+        [0x2002, ..] => Some(ipv4(2)),
+        _ => None,
+    }
+}
+
+/// Checks that a webhook URL is http or https, and unless private networks are allowed,
+/// that its host is neither a local name nor a non-public address
+///
+/// Host names are checked again once resolved, by the notifier's `PublicAddressResolver`.
+fn check_webhook_url(url: &str, allow_private_networks: bool) -> Result<(), String> {
+    let url = match reqwest::Url::parse(url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        _ => return Err("Invalid webhook_url. Expected an http or https URL".to_string()),
+    };
+    let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    if host.is_empty() {
+        return Err("Invalid webhook_url. Expected a host".to_string());
+    }
+    if allow_private_networks {
+        return Ok(());
+    }
+    let is_local_name = host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal");
+    let is_private_address = host.parse::<std::net::IpAddr>().is_ok_and(|ip| !is_public_address(ip));
+    if is_local_name || is_private_address {
+        return Err(format!("Invalid webhook_url. Host '{}' is not publicly routable", host));
+    }
+    Ok(())
+}
+
+// Resolves webhook hosts to their public addresses only, so that a host name cannot
+// point deliveries at loopback, private or metadata addresses
+struct PublicAddressResolver;
+
+impl reqwest::dns::Resolve for PublicAddressResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_address(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("Host '{}' does not resolve to a public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// The `X-Rewardo-Signature` header value: `sha256=` and the hex HMAC-SHA256 of the body
+pub fn webhook_signature(secret: &str, body: &[u8]) -> String {
+    use hmac::Mac;
+
+    let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Page size used when collecting every match of a saved search
+const WEBHOOK_PAGE_SIZE: usize = 200;
+
+// Sends new matches of saved searches to their webhooks
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    repo: web::Data<dyn RewardFlightRepository>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    airports: web::Data<AirportRegistry>,
+    client: reqwest::Client,
+    config: WebhookConfig,
+}
+
+impl WebhookNotifier {
+    pub fn new(
+        repo: web::Data<dyn RewardFlightRepository>,
+        watchlist: web::Data<dyn WatchlistRepository>,
+        airports: web::Data<AirportRegistry>,
+        config: WebhookConfig,
+    ) -> Self {
+        // Redirects are not followed, as they could lead to hosts that were never checked
+        let mut client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .redirect(reqwest::redirect::Policy::none());
+        if !config.allow_private_networks {
+            client = client.dns_resolver(std::sync::Arc::new(PublicAddressResolver));
+        }
+        let client = client.build().expect("HTTP client configuration is valid");
+        Self { repo, watchlist, airports, client, config }
+    }
+
+    pub fn config(&self) -> &WebhookConfig {
+        &self.config
+    }
+
+    /// Notifies every saved search with a webhook once per poll interval, forever
+    pub async fn run(self) {
+        loop {
+            actix_web::rt::time::sleep(self.config.poll_interval).await;
+
+            if let Err(e) = self.notify_all().await {
+                log::error!("Database error: {}", e);
+            }
+        }
+    }
+
+    /// Notifies every saved search with a webhook, up to `concurrency` at a time, so that
+    /// an endpoint that is slow or retrying does not hold up the other subscribers
+    pub async fn notify_all(&self) -> Result<(), sqlx::Error> {
+        let saved_searches = self.watchlist.find_all_saved_searches().await?;
+        futures_util::stream::iter(saved_searches.iter().filter(|saved_search| saved_search.webhook_url.is_some()))
+            .for_each_concurrent(self.config.concurrency, |saved_search| async move {
+                if let Err(message) = self.notify(saved_search).await {
+                    log::error!("Failed to notify saved search {}: {}", saved_search.id, message);
+                }
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Sends the saved search's matches that were not notified before to its webhook
+    ///
+    /// Returns the logged delivery, or `None` when there was nothing new to send.
+    pub async fn notify(&self, saved_search: &SavedSearch) -> Result<Option<WebhookDelivery>, String> {
+        let (Some(url), Some(secret)) = (&saved_search.webhook_url, &saved_search.webhook_secret) else {
+            return Ok(None);
+        };
+
+        // Collect every current match of the saved search
+        let city_pairs = saved_search.city_pairs(&self.airports)?;
+        let filter = saved_search.filter();
+        let mut flights = Vec::new();
+        let mut page_number = 0;
+        loop {
+            let page = self.repo.find_by_city_pairs_and_carrier_code_and_departure_between(
+                &city_pairs,
+                &saved_search.carrier_code,
+                saved_search.departure_from,
+                saved_search.departure_to,
+                &filter,
+                page_number,
+                WEBHOOK_PAGE_SIZE,
+            ).await.map_err(|e| e.to_string())?;
+            flights.extend(page.content);
+            page_number += 1;
+            if page_number >= page.total_pages {
+                break;
+            }
+        }
+
+        // Keep the flight, cabin and price combinations not sent before
+        let cabin_type = saved_search.cabin_type;
+        let matches: Vec<(NotifiedPrice, RewardFlightLatest)> = flights
+            .into_iter()
+            .filter_map(|flight| {
+                let price = NotifiedPrice {
+                    origin: flight.origin.clone(),
+                    destination: flight.destination.clone(),
+                    departure: flight.departure.parse().ok()?,
+                    cabin_type,
+                    points: flight.cabin_points_value(cabin_type)?,
+                };
+                Some((price, flight))
+            })
+            .collect();
+        let prices: Vec<NotifiedPrice> = matches.iter().map(|(price, _)| price.clone()).collect();
+        let claimed = self.watchlist
+            .claim_notified_prices(saved_search.id, &prices)
+            .await
+            .map_err(|e| e.to_string())?;
+        if claimed.is_empty() {
+            return Ok(None);
+        }
+
+        let claimed_prices: std::collections::HashSet<&NotifiedPrice> = claimed.iter().collect();
+        let payload = WebhookPayload {
+            saved_search_id: saved_search.id,
+            saved_search_name: saved_search.name.clone(),
+            sent_at: Utc::now(),
+            matches: matches
+                .into_iter()
+                .filter(|(price, _)| claimed_prices.contains(price))
+                .map(|(price, flight)| WebhookMatch {
+                    cabin_type,
+                    points: price.points,
+                    seats: flight.cabin_class_seat_count(cabin_type),
+                    saver: flight.is_saver_award(cabin_type),
+                    flight,
+                })
+                .collect(),
+        };
+
+        // Prices stay notified once delivered or dead-lettered, as a dead letter is only sent again when replayed;
+        // a delivery that could not be logged is retried on the next poll
+        let delivery = self.deliver(saved_search.id, url, secret, &payload).await;
+        if delivery.is_err() {
+            self.watchlist
+                .release_notified_prices(saved_search.id, &claimed)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        delivery.map(Some)
+    }
+
+    /// Sends a dead letter's payload to its saved search's webhook again and logs the delivery
+    ///
+    /// The dead letter is removed, so a replay that fails every attempt leaves only its own dead letter.
+    pub async fn replay(&self, dead_letter: &WebhookDeadLetter, saved_search: &SavedSearch) -> Result<WebhookDelivery, String> {
+        let (Some(url), Some(secret)) = (&saved_search.webhook_url, &saved_search.webhook_secret) else {
+            return Err(format!("Saved search {} has no webhook_url", saved_search.id));
+        };
+
+        let delivery = self.deliver(saved_search.id, url, secret, &dead_letter.payload).await?;
+        self.watchlist.delete_webhook_dead_letter(dead_letter.id).await.map_err(|e| e.to_string())?;
+        Ok(delivery)
+    }
+
+    /// POSTs the signed payload, retrying with exponential backoff, and logs the delivery;
+    /// a delivery failing every attempt is also dead-lettered
+    async fn deliver(&self, saved_search_id: i64, url: &str, secret: &str, payload: &impl Serialize) -> Result<WebhookDelivery, String> {
+        let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+        let signature = webhook_signature(secret, &body);
+
+        let mut attempts = 0;
+        let mut response_status = None;
+        let mut error = None;
+        // A URL that is not allowed fails without being attempted
+        let max_attempts = match check_webhook_url(url, self.config.allow_private_networks) {
+            Ok(()) => self.config.max_attempts,
+            Err(message) => {
+                error = Some(message);
+                0
+            }
+        };
+        while attempts < max_attempts {
+            if attempts > 0 {
+                actix_web::rt::time::sleep(self.config.backoff(attempts)).await;
+            }
+            attempts += 1;
+
+            let result = self.client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header("X-Rewardo-Signature", &signature)
+                .body(body.clone())
+                .send()
+                .await;
+            match result {
+                Ok(response) => {
+                    response_status = Some(response.status().as_u16() as i32);
+                    if response.status().is_success() {
+                        error = None;
+                        break;
+                    }
+                    error = Some(format!("Webhook responded with {}", response.status()));
+                },
+                Err(e) => {
+                    response_status = None;
+                    error = Some(e.to_string());
+                }
+            }
+            info!("Webhook delivery attempt {} to {} failed: {:?}", attempts, url, error);
+        }
+
+        let delivery = WebhookDelivery {
+            id: 0,
+            saved_search_id,
+            url: url.to_string(),
+            payload: serde_json::to_value(payload).map_err(|e| e.to_string())?,
+            status: if error.is_none() { DeliveryStatus::Delivered } else { DeliveryStatus::Failed },
+            attempts: attempts as i32,
+            response_status,
+            error,
+            created_at: Utc::now(),
+        };
+        let delivery = self.watchlist.create_webhook_delivery(&delivery).await.map_err(|e| e.to_string())?;
+        if delivery.status == DeliveryStatus::Failed {
+            self.watchlist.create_webhook_dead_letter(&delivery).await.map_err(|e| e.to_string())?;
+        }
+
+        Ok(delivery)
+    }
+}
+
+// Fans out newly landed scrapes and their availability changes to stream and live search subscribers
+#[derive(Clone)]
+pub struct ChangeBroadcaster {
+    sender: tokio::sync::broadcast::Sender<AvailabilityChange>,
+    scrapes: tokio::sync::watch::Sender<std::sync::Arc<Vec<ScrapedDeparture>>>,
+}
+
+impl ChangeBroadcaster {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = tokio::sync::broadcast::channel(capacity);
+        let (scrapes, _) = tokio::sync::watch::channel(std::sync::Arc::default());
+        Self { sender, scrapes }
+    }
+
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<AvailabilityChange> {
+        self.sender.subscribe()
+    }
+
+    /// Watches the departures scraped by the latest scrape to land
+    pub fn watch_scrapes(&self) -> tokio::sync::watch::Receiver<std::sync::Arc<Vec<ScrapedDeparture>>> {
+        self.scrapes.subscribe()
+    }
+
+    /// Polls the latest `scraped_at` forever, broadcasting the changes of every scrape that lands
+    ///
+    /// A single poller serves every subscriber, and the changes are only queried while
+    /// someone is subscribed.
+    pub async fn run(self, repo: web::Data<dyn RewardFlightRepository>, poll_interval: std::time::Duration) {
+        let mut watermark: Option<DateTime<Utc>> = None;
+        loop {
+            match repo.find_latest_scraped_at().await {
+                Ok(Some(latest)) => {
+                    if let Some(since) = watermark
+                        && latest > since
+                    {
+                        // The scraped departures are only queried while a live search watches them, and
+                        // before the changes, as sending them again for a retried window is harmless
+                        let departures = if self.scrapes.receiver_count() > 0 {
+                            match repo.find_departures_scraped_between(since, latest).await {
+                                Ok(departures) => departures,
+                                Err(e) => {
+                                    log::error!("Database error: {}", e);
+                                    actix_web::rt::time::sleep(poll_interval).await;
+                                    continue;
+                                }
+                            }
+                        } else {
+                            Vec::new()
+                        };
+                        if self.sender.receiver_count() > 0 {
+                            match repo.find_changes_scraped_between(since, latest).await {
+                                Ok(changes) => {
+                                    info!("Broadcasting {} change(s) scraped after {}", changes.len(), since);
+                                    for change in changes {
+                                        // Sending only fails when every subscriber has gone
+                                        let _ = self.sender.send(change);
+                                    }
+                                },
+                                Err(e) => {
+                                    log::error!("Database error: {}", e);
+                                    // Retry the same window on the next poll
+                                    actix_web::rt::time::sleep(poll_interval).await;
+                                    continue;
+                                }
+                            }
+                        }
+                        self.scrapes.send_replace(std::sync::Arc::new(departures));
+                    }
+                    watermark = Some(latest);
+                },
+                Ok(None) => {},
+                Err(e) => log::error!("Database error: {}", e),
+            }
+            actix_web::rt::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+// A change stream subscriber's carrier, route and change filter
+struct ChangeSubscription {
+    carrier_code: String,
+    origins: Option<Vec<String>>,
+    destinations: Option<Vec<String>>,
+    filter: ChangeFilter,
+}
+
+impl ChangeSubscription {
+    fn matches(&self, change: &AvailabilityChange) -> bool {
+        change.carrier_code == self.carrier_code
+            && self.origins.as_ref().is_none_or(|origins| origins.contains(&change.origin))
+            && self.destinations.as_ref().is_none_or(|destinations| destinations.contains(&change.destination))
+            && self.filter.matches(change)
+    }
+}
+
+// A message sent to a live search client: the initial results, then the flights added,
+// updated and removed as new scrapes land
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LiveSearchMessage {
+    Snapshot { page: Page<RewardFlightLatest> },
+    Added { flight: RewardFlightLatest },
+    Updated { flight: RewardFlightLatest },
+    Removed { flight: RewardFlightLatest },
+    Error { problem: Problem },
+}
+
+impl LiveSearchMessage {
+    /// The messages turning the previous results of a live search into the current ones
+    pub fn updates(previous: &[RewardFlightLatest], current: &[RewardFlightLatest]) -> Vec<LiveSearchMessage> {
+        let mut messages: Vec<LiveSearchMessage> = previous
+            .iter()
+            .filter(|flight| !current.iter().any(|other| other.is_same_flight(flight)))
+            .map(|flight| LiveSearchMessage::Removed { flight: flight.clone() })
+            .collect();
+        for flight in current {
+            match previous.iter().find(|other| other.is_same_flight(flight)) {
+                None => messages.push(LiveSearchMessage::Added { flight: flight.clone() }),
+                Some(other) if !other.has_same_availability(flight) => {
+                    messages.push(LiveSearchMessage::Updated { flight: flight.clone() })
+                },
+                Some(_) => {},
+            }
+        }
+        messages
+    }
+}
+
+/// Runs a live search session until the client disconnects
+///
+/// Every search definition the client sends replaces the previous one and is answered with a
+/// snapshot of the requested page of its results. Whenever a scrape lands the search is re-run
+/// across all of its pages and only the differences from the last results are sent, so a
+/// flight that merely moves to another page is neither removed nor added.
+async fn live_search_session(
+    mut session: actix_ws::Session,
+    mut messages: actix_ws::MessageStream,
+    mut scrapes: tokio::sync::watch::Receiver<std::sync::Arc<Vec<ScrapedDeparture>>>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carrier: Carrier,
+    airports: web::Data<AirportRegistry>,
+) {
+    let mut search: Option<LatestSearch> = None;
+    let mut results: Vec<RewardFlightLatest> = Vec::new();
+    scrapes.borrow_and_update();
+
+    loop {
+        let replies = tokio::select! {
+            message = messages.recv() => match message {
+                Some(Ok(actix_ws::Message::Text(text))) => {
+                    let parsed = serde_json::from_str::<LiveSearchRequest>(&text)
+                        .map_err(|e| Problem::bad_request(format!("Invalid search definition: {}", e)))
+                        .and_then(|request| request.search(&carrier, &airports).map_err(Problem::from));
+                    let started = match parsed {
+                        Ok(parsed) => start_live_search(repo.get_ref(), &parsed).await.map(|started| (parsed, started)),
+                        Err(problem) => Err(problem),
+                    };
+                    match started {
+                        Ok((parsed, (page, flights))) => {
+                            results = flights;
+                            search = Some(parsed);
+                            vec![LiveSearchMessage::Snapshot { page }]
+                        },
+                        Err(problem) => vec![LiveSearchMessage::Error { problem }],
+                    }
+                },
+                Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                    continue;
+                },
+                Some(Ok(actix_ws::Message::Close(reason))) => {
+                    let _ = session.close(reason).await;
+                    return;
+                },
+                Some(Ok(_)) => continue,
+                Some(Err(_)) | None => break,
+            },
+            changed = scrapes.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                let Some(search) = &search else {
+                    continue;
+                };
+
+                // Only the dates of the search's departures that the scrape touched are searched again
+                let departures: Vec<NaiveDate> = scrapes
+                    .borrow_and_update()
+                    .iter()
+                    .filter(|departure| search.covers(departure))
+                    .map(|departure| departure.departure)
+                    .collect();
+                let (Some(&first), Some(&last)) = (departures.iter().min(), departures.iter().max()) else {
+                    continue;
+                };
+                let window = search.departing_between(first, last);
+                match window.find_all(repo.get_ref()).await {
+                    Ok(flights) => {
+                        let (previous, others): (Vec<RewardFlightLatest>, Vec<RewardFlightLatest>) =
+                            std::mem::take(&mut results).into_iter().partition(|flight| window.departs_within(flight));
+                        let updates = LiveSearchMessage::updates(&previous, &flights);
+                        results = others;
+                        results.extend(flights);
+                        updates
+                    },
+                    Err(e) => {
+                        log::error!("Database error: {}", e);
+                        continue;
+                    }
+                }
+            },
+        };
+
+        for reply in replies {
+            let text = match serde_json::to_string(&reply) {
+                Ok(text) => text,
+                Err(e) => {
+                    log::error!("Failed to serialize live search message: {}", e);
+                    continue;
+                }
+            };
+            if session.text(text).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    let _ = session.close(None).await;
+}
+
+/// Runs a new live search for its snapshot page and every flight its updates are diffed against,
+/// rejecting searches that match more flights than a live search follows
+async fn start_live_search(
+    repo: &dyn RewardFlightRepository,
+    search: &LatestSearch,
+) -> Result<(Page<RewardFlightLatest>, Vec<RewardFlightLatest>), Problem> {
+    let database_error = |e: sqlx::Error| {
+        log::error!("Database error: {}", e);
+        Problem::internal_server_error("Failed to fetch reward flights")
+    };
+
+    let page = search.find(repo).await.map_err(database_error)?;
+    if page.total_elements > MAX_LIVE_SEARCH_RESULTS {
+        return Err(Problem::bad_request(format!(
+            "Live search matches {} flights, more than the {} it can follow. Narrow its route, dates or filters",
+            page.total_elements, MAX_LIVE_SEARCH_RESULTS
+        )));
+    }
+    let flights = search.find_all(repo).await.map_err(database_error)?;
+    Ok((page, flights))
+}
+
+/// Handler for retrieving the latest reward flights based on search criteria
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `from` - The start date for the search in YYYY-MM-DD format
+/// * `to` - The end date for the search in YYYY-MM-DD format
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+/// * `cabin` - The cabin type the seat, saver and points filters apply to (default: any cabin)
+/// * `min-seats` (or `passengers`) - Only return flights with at least this many seats
+/// * `saver-only` - Only include saver awards (default: false)
+/// * `min-points` / `max-points` - Only include awards within this points range
+/// * `weekdays` - Comma-separated departure days to include (e.g., "THU,FRI")
+/// * `weekends-only` - Only include Saturday and Sunday departures (default: false)
+/// * `deal-score` - Score each cabin price against the route's last 90 days of history (default: false)
+/// * `format` - `json` (default) or `csv`, which can also be requested with `Accept: text/csv`
+/// * `all` - Export every matching flight rather than one page, as CSV only (default: false)
+///
+/// # Returns
+/// A paginated list of reward flights matching the criteria, each with its concrete airports,
+/// or the flights as CSV with one row per flight
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, ExportParams),
+    responses(
+        (status = 200, description = "A page of the latest reward flights", content(
+            (Page<RewardFlightLatest> = "application/json"),
+            (String = "text/csv")
+        )),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/from/{from}/to/{to}")]
+async fn latest_reward_flights(
+    req: HttpRequest,
+    path: web::Path<(String, String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    export: web::Query<ExportParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, from, to) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    let search = match LatestSearch::new(carrier, &origin, &destination, &from, &to, &query, &airports) {
+        Ok(search) => search,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let format = match export.format(&req) {
+        Ok(format) => format,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    let result = match format {
+        ExportFormat::Json => search.find(repo.get_ref()).await.map(|page| HttpResponse::Ok().json(page)),
+        ExportFormat::Csv if export.all() => csv_export("reward-flights.csv", search.stream(repo.get_ref())).await,
+        ExportFormat::Csv => match search.find(repo.get_ref()).await {
+            Ok(page) => csv_export("reward-flights.csv", page_rows(page)).await,
+            Err(e) => Err(e),
+        },
+    };
+
+    match result {
+        Ok(response) => response,
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch reward flights").error_response()
+        }
+    }
+}
+
+/// Health check endpoint that returns a 200 OK response
+///
+/// # Returns
+/// A 200 OK response with a simple message
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "The service is up", body = String, content_type = "text/plain")
+    )
+)]
+#[get("/health")]
+async fn health_check() -> impl Responder {
+    HttpResponse::Ok().body("OK")
+}
+
+/// Handler for retrieving the cheapest reward flights based on origin, destination, and cabin type
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST), which the carrier must support
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 50)
+/// * `min-seats` (or `passengers`) - Only return flights with at least this many seats in the cabin
+/// * `saver-only` - Only include saver awards (default: false)
+/// * `min-points` / `max-points` - Only include awards within this points range
+/// * `weekdays` - Comma-separated departure days to include (e.g., "THU,FRI")
+/// * `weekends-only` - Only include Saturday and Sunday departures (default: false)
+/// * `deal-score` - Score each cabin price against the route's last 90 days of history (default: false)
+/// * `format` - `json` (default) or `csv`, which can also be requested with `Accept: text/csv`
+/// * `all` - Export every matching flight rather than one page, as CSV only (default: false)
+///
+/// # Returns
+/// A paginated list of reward flights ordered by lowest cabin points, or the flights as CSV
+/// with one row per flight
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, ExportParams),
+    responses(
+        (status = 200, description = "A page of reward flights ordered by lowest cabin points", content(
+            (Page<RewardFlightLatest> = "application/json"),
+            (String = "text/csv")
+        )),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/cheapest")]
+async fn cheapest_reward_flights(
+    req: HttpRequest,
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    export: web::Query<ExportParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, cabin_type_str) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    // Expand metro area codes to their airports
+    let origins = match expand_airport_codes(&airports, &[origin]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destinations = match expand_airport_codes(&airports, &[destination]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let city_pairs = city_pairs(&origins, &destinations);
+    let (page_number, page_size) = match query.page(50) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    
+    // Validate cabin type
+    let cabin_type = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    if !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+    let format = match export.format(&req) {
+        Ok(format) => format,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    if format == ExportFormat::Csv {
+        let rows = if export.all() {
+            Ok(repo.stream_all_by_city_pairs_ordered_by_lowest_cabin_points(
+                &city_pairs,
+                &carrier.code,
+                cabin_type,
+                &filter,
+            ))
+        } else {
+            repo.find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+                &city_pairs,
+                &carrier.code,
+                cabin_type,
+                &filter,
+                page_number,
+                page_size,
+            ).await.map(page_rows)
+        };
+        let result = match rows {
+            Ok(rows) => csv_export("cheapest-reward-flights.csv", rows).await,
+            Err(e) => Err(e),
+        };
+        return match result {
+            Ok(response) => response,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                Problem::internal_server_error("Failed to fetch cheapest reward flights").error_response()
+            }
+        };
+    }
+
+    // Query the repository
+    let result = match repo.find_all_by_city_pairs_ordered_by_lowest_cabin_points(
+        &city_pairs,
+        &carrier.code,
+        cabin_type,
+        &filter,
+        page_number,
+        page_size,
+    ).await {
+        Ok(mut page) if query.deal_score.unwrap_or(false) => {
+            attach_deal_scores(repo.get_ref(), &carrier.code, page.content.iter_mut().collect()).await.map(|_| page)
+        },
+        result => result,
+    };
+
+    match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch cheapest reward flights").error_response()
+        }
+    }
+}
+
+/// Handler for retrieving historic reward flights for a specific date
+///
+/// The snapshots are of a single flight, so metro area codes are rejected rather than expanded.
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport code (e.g., "LHR"), not a metro area code
+/// * `destination` - The destination airport code (e.g., "JFK"), not a metro area code
+/// * `on` - The specific date for the flight in YYYY-MM-DD format
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+/// * `cabin` - The cabin type the seat, saver and points filters apply to (default: any cabin)
+/// * `min-seats` (or `passengers`) - Only return snapshots with at least this many seats
+/// * `saver-only` - Only include snapshots with saver awards (default: false)
+/// * `min-points` / `max-points` - Only include snapshots with awards within this points range
+/// * `weekdays` / `weekends-only` - Return no snapshots unless the flight departs on one of these days
+/// * `format` - `json` (default) or `csv`, which can also be requested with `Accept: text/csv`
+/// * `all` - Export every snapshot rather than one page, as CSV only (default: false)
+///
+/// # Returns
+/// A paginated list of historic reward flights for the specified date ordered by scraped_at ascending,
+/// or the snapshots as CSV with one row per snapshot
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, ExportParams),
+    responses(
+        (status = 200, description = "A page of historic snapshots of the flight, oldest first", content(
+            (Page<RewardFlightLatestHistoric> = "application/json"),
+            (String = "text/csv")
+        )),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/on/{on}/historic")]
+async fn historic_reward_flights(
+    req: HttpRequest,
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    export: web::Query<ExportParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, on) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origin = match resolve_airport_code(&airports, &origin) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destination = match resolve_airport_code(&airports, &destination) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    
+    // Parse date
+    let departure_date = match NaiveDate::parse_from_str(&on, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("on", "Invalid date format. Expected YYYY-MM-DD").error_response(),
+    };
+    let format = match export.format(&req) {
+        Ok(format) => format,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    if format == ExportFormat::Csv {
+        let rows = if export.all() {
+            Ok(repo.stream_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                &origin,
+                &destination,
+                &carrier.code,
+                departure_date,
+                &filter,
+            ))
+        } else {
+            repo.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+                &origin,
+                &destination,
+                &carrier.code,
+                departure_date,
+                &filter,
+                page_number,
+                page_size,
+            ).await.map(page_rows)
+        };
+        let result = match rows {
+            Ok(rows) => csv_export("historic-reward-flights.csv", rows).await,
+            Err(e) => Err(e),
+        };
+        return match result {
+            Ok(response) => response,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                Problem::internal_server_error("Failed to fetch historic reward flights").error_response()
+            }
+        };
+    }
+
+    // Query the repository
+    match repo.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
+        &origin,
+        &destination,
+        &carrier.code,
+        departure_date,
+        &filter,
+        page_number,
+        page_size,
+    ).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch historic reward flights").error_response()
+        }
+    }
+}
+
+/// Handler for retrieving the price history of a route on a specific date, per cabin
+///
+/// The series follow a single flight, so metro area codes are rejected rather than expanded:
+/// merging the points and seats of several airports' flights would describe none of them.
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport code (e.g., "LHR"), not a metro area code
+/// * `destination` - The destination airport code (e.g., "JFK"), not a metro area code
+/// * `on` - The departure date in YYYY-MM-DD format
+/// * `cabin` - Only return the series of this cabin (default: every cabin the carrier supports)
+/// * `interval` - `raw` (default) for every scrape, or `hourly` / `daily` buckets
+///
+/// # Returns
+/// One series of (scraped_at, points, seats, saver) per cabin, oldest first
+#[utoipa::path(
+    tag = "history",
+    params(PriceHistoryParams),
+    responses(
+        (status = 200, description = "Per-cabin price series of the flight", body = PriceHistory),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/on/{on}/price-history")]
+async fn price_history_reward_flights(
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<PriceHistoryParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, on) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origin = match resolve_airport_code(&airports, &origin) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destination = match resolve_airport_code(&airports, &destination) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    if let Some(cabin_type) = query.cabin && !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+    let interval = query.interval.unwrap_or_default();
+
+    // Parse date
+    let departure_date = match NaiveDate::parse_from_str(&on, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("on", "Invalid date format. Expected YYYY-MM-DD").error_response(),
+    };
+
+    match repo.find_price_history_by_origin_and_destination_and_carrier_code_and_departure(
+        &origin,
+        &destination,
+        &carrier.code,
+        departure_date,
+        interval,
+    ).await {
+        Ok(cabins) => HttpResponse::Ok().json(PriceHistory {
+            origin,
+            destination,
+            carrier_code: carrier.code.clone(),
+            departure: departure_date,
+            interval,
+            cabins: cabins
+                .into_iter()
+                .filter(|series| carrier.supports_cabin(series.cabin_type))
+                .filter(|series| query.cabin.is_none_or(|cabin_type| cabin_type == series.cabin_type))
+                .collect(),
+        }),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch price history").error_response()
+        }
+    }
+}
+
+/// Handler for the feed of availability changes between consecutive scrapes of a route's flights
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `from` - The first departure date in YYYY-MM-DD format
+/// * `to` - The last departure date in YYYY-MM-DD format
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+/// * `cabin` - Only report changes in this cabin (default: every cabin)
+/// * `event-types` - Comma-separated event types to report (e.g., "SEATS_OPENED,SAVER_CHANGED")
+/// * `since` - Only report changes scraped at or after this RFC 3339 timestamp
+///
+/// # Returns
+/// A paginated list of availability changes, newest first
+#[utoipa::path(
+    tag = "history",
+    params(ChangeParams),
+    responses(
+        (status = 200, description = "A page of availability changes", body = Page<AvailabilityChange>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/from/{from}/to/{to}/changes")]
+async fn reward_flight_changes(
+    path: web::Path<(String, String, String, String, String)>,
+    query: web::Query<ChangeParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, from, to) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    // Expand metro area codes to their airports
+    let origins = match expand_airport_codes(&airports, &[origin]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destinations = match expand_airport_codes(&airports, &[destination]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    if let Some(cabin_type) = filter.cabin_type && !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+
+    // Parse dates
+    let from_date = match NaiveDate::parse_from_str(&from, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("from", "Invalid 'from' date format. Expected YYYY-MM-DD").error_response(),
+    };
+
+    let to_date = match NaiveDate::parse_from_str(&to, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("to", "Invalid 'to' date format. Expected YYYY-MM-DD").error_response(),
+    };
+
+    match repo.find_changes_by_city_pairs_and_carrier_code_and_departure_between(
+        &city_pairs(&origins, &destinations),
+        &carrier.code,
+        from_date,
+        to_date,
+        &filter,
+        page_number,
+        page_size,
+    ).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch availability changes").error_response()
+        }
+    }
+}
+
+/// Handler for the points distribution and availability of a route's cabin over a lookback window
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport code (e.g., "LHR")
+/// * `destination` - The destination airport code (e.g., "JFK")
+/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST), which the carrier must support
+/// * `lookback-days` - How many days of scrape history to include (default: 90, max: 365)
+///
+/// # Returns
+/// The min/median/p90/max points, saver departure share and average seats of the cabin, where
+/// points and seats are of the snapshots with bookable seats and the share is of every scraped departure
+#[utoipa::path(
+    tag = "history",
+    params(StatisticsParams),
+    responses(
+        (status = 200, description = "Points and availability statistics of the route and cabin", body = RouteStatistics),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/statistics")]
+async fn route_statistics(
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<StatisticsParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, cabin_type_str) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origin = match resolve_airport_code(&airports, &origin) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destination = match resolve_airport_code(&airports, &destination) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let cabin_type = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    if !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+    let lookback_days = query.lookback_days.unwrap_or(DEFAULT_LOOKBACK_DAYS);
+    if lookback_days == 0 || lookback_days > MAX_LOOKBACK_DAYS {
+        return Problem::invalid_field("lookback-days", format!("Invalid lookback-days. Expected between 1 and {}", MAX_LOOKBACK_DAYS)).error_response();
+    }
+
+    match repo.find_statistics_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &origin,
+        &destination,
+        &carrier.code,
+        cabin_type,
+        lookback_days,
+    ).await {
+        Ok(statistics) => HttpResponse::Ok().json(statistics),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch route statistics").error_response()
+        }
+    }
+}
+
+/// Handler for when saver seats of a route's cabin typically appear and disappear
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport code (e.g., "LHR")
+/// * `destination` - The destination airport code (e.g., "JFK")
+/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST), which the carrier must support
+/// * `lookback-days` - How many days of scrape history to include (default: 90, max: 365)
+///
+/// # Returns
+/// The days before departure at which saver seats were released and withdrawn, with a histogram of each
+#[utoipa::path(
+    tag = "history",
+    params(StatisticsParams),
+    responses(
+        (status = 200, description = "When saver seats are typically released and withdrawn", body = ReleasePattern),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/release-pattern")]
+async fn release_pattern(
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<StatisticsParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, cabin_type_str) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origin = match resolve_airport_code(&airports, &origin) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destination = match resolve_airport_code(&airports, &destination) {
+        Ok(code) => code,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let cabin_type = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    if !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+    let lookback_days = query.lookback_days.unwrap_or(DEFAULT_LOOKBACK_DAYS);
+    if lookback_days == 0 || lookback_days > MAX_LOOKBACK_DAYS {
+        return Problem::invalid_field("lookback-days", format!("Invalid lookback-days. Expected between 1 and {}", MAX_LOOKBACK_DAYS)).error_response();
+    }
+
+    match repo.find_release_pattern_by_origin_and_destination_and_carrier_code_and_cabin_type(
+        &origin,
+        &destination,
+        &carrier.code,
+        cabin_type,
+        lookback_days,
+    ).await {
+        Ok(pattern) => HttpResponse::Ok().json(pattern),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch release pattern").error_response()
+        }
+    }
+}
+
+/// Handler for a Server-Sent Events stream of availability changes as new scrapes land
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - Only stream changes from this airport or metro area code (default: every origin)
+/// * `destination` - Only stream changes to this airport or metro area code (default: every destination)
+/// * `cabin` - Only stream changes in this cabin (default: every cabin)
+/// * `event-types` - Comma-separated event types to stream (e.g., "SEATS_OPENED,SAVER_CHANGED")
+///
+/// # Returns
+/// A `text/event-stream` of availability changes, each named after its event type, with
+/// keep-alive comments while idle
+#[utoipa::path(
+    tag = "history",
+    params(ChangeStreamParams),
+    responses(
+        (status = 200, description = "A server-sent event stream of availability changes", body = AvailabilityChange, content_type = "text/event-stream"),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/changes/stream")]
+async fn stream_reward_flight_changes(
+    path: web::Path<String>,
+    query: web::Query<ChangeStreamParams>,
+    changes: web::Data<ChangeBroadcaster>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let carrier = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    // Expand metro area codes to their airports
+    let origins = match query.origin.as_ref().map(|origin| expand_airport_codes(&airports, std::slice::from_ref(origin))).transpose() {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destinations = match query.destination.as_ref().map(|destination| expand_airport_codes(&airports, std::slice::from_ref(destination))).transpose() {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+    let event_types = match parse_event_types(query.event_types.as_deref()) {
+        Ok(event_types) => event_types,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    if let Some(cabin_type) = query.cabin && !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+
+    let subscription = ChangeSubscription {
+        carrier_code: carrier.code.clone(),
+        origins,
+        destinations,
+        filter: ChangeFilter {
+            cabin_type: query.cabin,
+            event_types,
+            since: None,
+        },
+    };
+
+    let stream = futures_util::stream::unfold((changes.subscribe(), subscription), |(mut receiver, subscription)| async move {
+        loop {
+            let event = match actix_web::rt::time::timeout(CHANGE_STREAM_KEEP_ALIVE, receiver.recv()).await {
+                Err(_) => web::Bytes::from_static(b": keep-alive\n\n"),
+                Ok(Ok(change)) if subscription.matches(&change) => match serde_json::to_string(&change) {
+                    Ok(data) => web::Bytes::from(format!("event: {}\ndata: {}\n\n", change.event_type.as_str(), data)),
+                    Err(e) => {
+                        log::error!("Failed to serialize availability change: {}", e);
+                        continue;
+                    }
+                },
+                Ok(Ok(_)) => continue,
+                Ok(Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped))) => {
+                    web::Bytes::from(format!(": skipped {} change(s)\n\n", skipped))
+                },
+                Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) => return None,
             };
-            
-            flights.push(flight);
+            return Some((Ok::<_, actix_web::Error>(event), (receiver, subscription)));
+        }
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream)
+}
+
+/// Handler for a WebSocket live search of the latest reward flights
+///
+/// Once connected, the client sends a search definition as a JSON text message: `origin`,
+/// `destination`, `from` and `to` as in the latest reward flights path, plus any of its query
+/// parameters (e.g., `{"origin": "LON", "destination": "NYC", "from": "2026-11-01",
+/// "to": "2026-11-30", "cabin": "BUSINESS", "min-seats": 2}`). Sending another definition
+/// replaces the search.
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+///
+/// # Returns
+/// A WebSocket answering each search definition with a `SNAPSHOT` of the requested page of its
+/// results, followed by `ADDED`, `UPDATED` and `REMOVED` flights as new scrapes land, or an
+/// `ERROR` for invalid searches or those matching more than 2000 flights. Updates cover every
+/// page of the search rather than the snapshot's page alone, so a flight moving between pages is
+/// not reported as removed and added, and only the departures each scrape touched are searched again.
+#[utoipa::path(
+    tag = "reward-flights",
+    responses(
+        (status = 101, description = "Switches to a WebSocket of live search messages", body = LiveSearchMessage),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/live")]
+async fn live_reward_flights(
+    req: HttpRequest,
+    body: web::Payload,
+    path: web::Path<String>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    changes: web::Data<ChangeBroadcaster>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let carrier = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier.clone(),
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    let (response, session, messages) = match actix_ws::handle(&req, body) {
+        Ok(handshake) => handshake,
+        Err(e) => return Problem::bad_request(format!("Invalid WebSocket handshake: {}", e)).error_response(),
+    };
+    actix_web::rt::spawn(live_search_session(session, messages, changes.watch_scrapes(), repo, carrier, airports));
+
+    response
+}
+
+/// Handler for exploring the cheapest reward flight to every destination from an origin
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST), which the carrier must support
+/// * `from` - The start date for the search in YYYY-MM-DD format
+/// * `to` - The end date for the search in YYYY-MM-DD format
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 50)
+/// * `sort` - Order destinations by `points` (default) or by `departure`
+/// * `min-seats` (or `passengers`) - Only consider flights with at least this many seats in the cabin
+/// * `saver-only` - Only include saver awards (default: false)
+/// * `min-points` / `max-points` - Only include awards within this points range
+/// * `weekdays` - Comma-separated departure days to include (e.g., "THU,FRI")
+/// * `weekends-only` - Only include Saturday and Sunday departures (default: false)
+/// * `deal-score` - Score each cabin price against the route's last 90 days of history (default: false)
+///
+/// # Returns
+/// A paginated list with the cheapest available reward flight per destination
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, ExploreParams),
+    responses(
+        (status = 200, description = "A page of the cheapest reward flight to each destination", body = Page<RewardFlightLatest>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/cabin/{cabin_type}/from/{from}/to/{to}/explore")]
+async fn explore_reward_flights(
+    path: web::Path<(String, String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    explore: web::Query<ExploreParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, cabin_type_str, from, to) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origins = match expand_airport_codes(&airports, &[origin]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let (page_number, page_size) = match query.page(50) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let sort = explore.sort.unwrap_or_default();
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    // Validate cabin type
+    let cabin_type = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    if !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+
+    // Parse dates
+    let from_date = match NaiveDate::parse_from_str(&from, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("from", "Invalid 'from' date format. Expected YYYY-MM-DD").error_response(),
+    };
+
+    let to_date = match NaiveDate::parse_from_str(&to, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("to", "Invalid 'to' date format. Expected YYYY-MM-DD").error_response(),
+    };
+
+    // Query the repository
+    let result = match repo.find_cheapest_per_destination_by_origins_and_carrier_code_and_departure_between(
+        &origins,
+        &carrier.code,
+        cabin_type,
+        from_date,
+        to_date,
+        sort,
+        &filter,
+        page_number,
+        page_size,
+    ).await {
+        Ok(mut page) if query.deal_score.unwrap_or(false) => {
+            attach_deal_scores(repo.get_ref(), &carrier.code, page.content.iter_mut().collect()).await.map(|_| page)
+        },
+        result => result,
+    };
+
+    match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to explore reward flights").error_response()
+        }
+    }
+}
+
+/// Handler for pairing outbound and return reward flights into round trips
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `cabinType` - The outbound cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST)
+/// * `outbound-from` / `outbound-to` - The outbound departure window in YYYY-MM-DD format
+/// * `return-from` / `return-to` - The return departure window in YYYY-MM-DD format
+/// * `return-cabin` - The return cabin type (default: the outbound cabin type)
+/// * `return-origins` - Comma-separated airports the return may depart from, for open-jaw trips (default: `destination`)
+/// * `return-destinations` - Comma-separated airports the return may arrive at, for open-jaw trips (default: `origin`)
+/// * `min-trip-days` - The minimum number of days between departures (default: 1)
+/// * `max-trip-days` - The maximum number of days between departures (default: 30)
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+/// * `min-seats` (or `passengers`) - Only pair flights with at least this many seats in each leg's cabin
+/// * `saver-only` - Only include saver awards (default: false)
+/// * `min-points` / `max-points` - Only include awards within this points range
+/// * `weekdays` - Comma-separated outbound departure days to include (e.g., "THU,FRI")
+/// * `weekends-only` - Only include Saturday and Sunday outbound departures (default: false)
+/// * `deal-score` - Score each cabin price against the route's last 90 days of history (default: false)
+///
+/// # Returns
+/// A paginated list of round trips (or open-jaw itineraries) ordered by total points, each with its concrete airports
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, RoundTripParams),
+    responses(
+        (status = 200, description = "A page of round trips ordered by total points", body = Page<RewardFlightRoundTrip>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/round-trip")]
+async fn round_trip_reward_flights(
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    round_trip: web::Query<RoundTripParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, cabin_type_str) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let min_trip_days = round_trip.min_trip_days.unwrap_or(1);
+    let max_trip_days = round_trip.max_trip_days.unwrap_or(30);
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    if min_trip_days < 0 || min_trip_days > max_trip_days {
+        return Problem::invalid_field("min-trip-days", "Invalid trip length. Expected 0 <= min-trip-days <= max-trip-days").error_response();
+    }
+
+    // Validate cabin types
+    let outbound_cabin = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    let return_cabin = match round_trip.return_cabin.as_deref().map(str::parse::<CabinType>) {
+        Some(Ok(cabin_type)) => cabin_type,
+        Some(Err(message)) => return Problem::invalid_field("return-cabin", message).error_response(),
+        None => outbound_cabin,
+    };
+    for cabin_type in [outbound_cabin, return_cabin] {
+        if !carrier.supports_cabin(cabin_type) {
+            return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+        }
+    }
+
+    // Parse dates
+    let mut dates = Vec::new();
+    for (name, value) in [
+        ("outbound-from", &round_trip.outbound_from),
+        ("outbound-to", &round_trip.outbound_to),
+        ("return-from", &round_trip.return_from),
+        ("return-to", &round_trip.return_to),
+    ] {
+        match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) => dates.push(date),
+            Err(_) => return Problem::invalid_field(name, format!("Invalid '{}' date format. Expected YYYY-MM-DD", name)).error_response(),
+        }
+    }
+
+    // Every combination of the (metro area expanded) airports is searched at once
+    let mut routes = Vec::new();
+    for ((origin_field, origins), (destination_field, destinations)) in [
+        (("origin", vec![origin.clone()]), ("destination", vec![destination.clone()])),
+        (
+            ("return-origins", split_airport_codes(round_trip.return_origins.as_deref(), &destination)),
+            ("return-destinations", split_airport_codes(round_trip.return_destinations.as_deref(), &origin)),
+        ),
+    ] {
+        let origins = match expand_airport_codes(&airports, &origins) {
+            Ok(codes) => codes,
+            Err(message) => return Problem::invalid_field(origin_field, message).error_response(),
+        };
+        let destinations = match expand_airport_codes(&airports, &destinations) {
+            Ok(codes) => codes,
+            Err(message) => return Problem::invalid_field(destination_field, message).error_response(),
+        };
+        let pairs = city_pairs(&origins, &destinations);
+        if pairs.is_empty() || pairs.len() > MAX_CITY_PAIRS {
+            return Problem::bad_request(format!("Invalid number of routes. Expected between 1 and {} combinations of origins and destinations in each direction", MAX_CITY_PAIRS)).error_response();
+        }
+        routes.push(pairs);
+    }
+    let return_pairs = routes.pop().unwrap_or_default();
+    let outbound_pairs = routes.pop().unwrap_or_default();
+
+    let search = RoundTripSearch {
+        outbound_cabin,
+        return_cabin,
+        outbound_from: dates[0],
+        outbound_to: dates[1],
+        return_from: dates[2],
+        return_to: dates[3],
+        min_trip_days,
+        max_trip_days,
+    };
+
+    // Query the repository
+    let result = match repo.find_itineraries_by_city_pairs_and_carrier_code(
+        &outbound_pairs,
+        &return_pairs,
+        &carrier.code,
+        &search,
+        &filter,
+        page_number,
+        page_size,
+    ).await {
+        Ok(mut page) if query.deal_score.unwrap_or(false) => {
+            let flights = page.content
+                .iter_mut()
+                .flat_map(|round_trip| [&mut round_trip.outbound, &mut round_trip.inbound])
+                .collect();
+            attach_deal_scores(repo.get_ref(), &carrier.code, flights).await.map(|_| page)
+        },
+        result => result,
+    };
+
+    match result {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch round trip reward flights").error_response()
+        }
+    }
+}
+
+/// Handler for retrieving a route's availability calendar for a month
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `month` - The month in YYYY-MM format
+/// * `cabin` - The cabin type the seat, saver and points filters apply to (default: any cabin)
+/// * `min-seats` (or `passengers`) - Only count awards with at least this many seats
+/// * `saver-only` - Only count saver awards (default: false)
+/// * `min-points` / `max-points` - Only count awards within this points range
+/// * `weekdays` - Comma-separated departure days to count (e.g., "THU,FRI")
+/// * `weekends-only` - Only count Saturday and Sunday departures (default: false)
+///
+/// # Returns
+/// One cell per day of the month with the lowest points, max seats and saver availability per cabin,
+/// where days excluded by the departure day filters are empty. The calendar is not paginated or
+/// scored, so any other query parameter is rejected
+#[utoipa::path(
+    tag = "reward-flights",
+    params(CalendarParams),
+    responses(
+        (status = 200, description = "The availability of every day of the month", body = RewardFlightCalendar),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/calendar/{month}")]
+async fn calendar_reward_flights(
+    path: web::Path<(String, String, String, String)>,
+    query: web::Query<CalendarParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, month) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let filter = match query.filter() {
+        Ok(filter) => filter,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+    let origins = match expand_airport_codes(&airports, std::slice::from_ref(&origin)) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destinations = match expand_airport_codes(&airports, std::slice::from_ref(&destination)) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+
+    // Parse month into its first and last day
+    let first_day = match NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("month", "Invalid month format. Expected YYYY-MM").error_response(),
+    };
+    let last_day = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|date| date.pred_opt())
+        .unwrap_or(first_day);
+
+    // Query the repository
+    match repo.find_calendar_by_origins_and_destinations_and_carrier_code_and_departure_between(
+        &origins,
+        &destinations,
+        &carrier.code,
+        first_day,
+        last_day,
+        &filter,
+    ).await {
+        Ok(days) => {
+            // Fill in the days without any availability
+            let mut days: HashMap<NaiveDate, CalendarDay> = days
+                .into_iter()
+                .map(|day| (day.date, day))
+                .collect();
+            let days = first_day
+                .iter_days()
+                .take_while(|date| *date <= last_day)
+                .map(|date| days.remove(&date).unwrap_or_else(|| CalendarDay::empty(date)))
+                .collect();
+
+            HttpResponse::Ok().json(RewardFlightCalendar {
+                origin: origin.to_uppercase(),
+                destination: destination.to_uppercase(),
+                carrier_code: carrier.code.clone(),
+                month: first_day.format("%Y-%m").to_string(),
+                days,
+            })
+        },
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch reward flight calendar").error_response()
+        }
+    }
+}
+
+/// Handler for a flexible date search around a target date
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+/// * `destination` - The destination airport or metro area code (e.g., "JFK" or "NYC")
+/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS, FIRST), which the carrier must support
+/// * `date` - The target departure date in YYYY-MM-DD format
+/// * `flex-days` - The number of days either side of the target date to search (default: 3, max: 14)
+/// * `min-seats` (or `passengers`), `saver-only`, `min-points`, `max-points`, `weekdays`, `weekends-only`, `deal-score` - As for the latest search
+///
+/// # Returns
+/// The cheapest flight per day in the window, ordered by points then proximity to the target date
+#[utoipa::path(
+    tag = "reward-flights",
+    params(SearchParams, FlexibleParams),
+    responses(
+        (status = 200, description = "The best option of every day around the target date", body = FlexibleDateSearch),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/around/{date}")]
+async fn flexible_reward_flights(
+    path: web::Path<(String, String, String, String, String)>,
+    query: web::Query<SearchParams>,
+    flexible: web::Query<FlexibleParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin, destination, cabin_type_str, date) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    // Expand metro area codes to their airports
+    let origins = match expand_airport_codes(&airports, &[origin]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+    let destinations = match expand_airport_codes(&airports, &[destination]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("destination", message).error_response(),
+    };
+
+    // Validate cabin type, which the filters then apply to
+    let cabin_type = match cabin_type_str.parse::<CabinType>() {
+        Ok(cabin_type) => cabin_type,
+        Err(message) => return Problem::invalid_field("cabin_type", message).error_response(),
+    };
+    if !carrier.supports_cabin(cabin_type) {
+        return Problem::invalid_field("cabin_type", format!("Cabin type {} is not supported by {}", cabin_type.as_str(), carrier.display_name)).error_response();
+    }
+    let filter = match query.filter() {
+        Ok(filter) => SearchFilter { cabin_type: Some(cabin_type), ..filter },
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    let flex_days = flexible.flex_days.unwrap_or(3);
+    if flex_days > MAX_FLEX_DAYS {
+        return Problem::invalid_field("flex-days", format!("Invalid flex-days. Expected at most {}", MAX_FLEX_DAYS)).error_response();
+    }
+
+    // Parse the date the window is around
+    let target_date = match NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => return Problem::invalid_field("date", "Invalid date format. Expected YYYY-MM-DD").error_response(),
+    };
+    if FlexibleDateSearch::window(target_date, flex_days).is_none() {
+        return Problem::invalid_field("date", "Invalid date. The flexible window falls outside the supported dates").error_response();
+    }
+
+    // Search the cheapest flight of every day in the window, across every airport pair for metro areas
+    let mut search = match repo.find_flexible_dates_by_city_pairs_and_carrier_code_and_cabin_type(
+        &city_pairs(&origins, &destinations),
+        &carrier.code,
+        cabin_type,
+        target_date,
+        flex_days,
+        &filter,
+    ).await {
+        Ok(search) => search,
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch flexible reward flights").error_response();
+        }
+    };
+    if query.deal_score.unwrap_or(false) {
+        let flights = search.options.iter_mut().map(|option| &mut option.flight).collect();
+        if let Err(e) = attach_deal_scores(repo.get_ref(), &carrier.code, flights).await {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch flexible reward flights").error_response();
+        }
+    }
+
+    HttpResponse::Ok().json(search)
+}
+
+/// Handler for listing the carriers with scraped reward flights
+///
+/// # Returns
+/// Every carrier in the latest data with its flight count, latest scrape and departure range
+#[utoipa::path(
+    tag = "routes",
+    responses(
+        (status = 200, description = "Every carrier with scraped reward flights", body = Vec<AvailabilitySummary>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airlines")]
+async fn carrier_summaries(
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+) -> impl Responder {
+    match repo.find_carrier_summaries().await {
+        Ok(summaries) => {
+            let summaries: Vec<AvailabilitySummary> = summaries
+                .into_iter()
+                .map(|summary| AvailabilitySummary {
+                    name: carriers.find(&summary.code).map(|carrier| carrier.display_name.clone()),
+                    ..summary
+                })
+                .collect();
+            HttpResponse::Ok().json(summaries)
+        },
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch carriers").error_response()
+        }
+    }
+}
+
+/// Handler for listing the origins a carrier has scraped reward flights from
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+///
+/// # Returns
+/// Every origin in the latest data with its flight count, latest scrape and departure range
+#[utoipa::path(
+    tag = "routes",
+    responses(
+        (status = 200, description = "Every origin of the carrier", body = Vec<AvailabilitySummary>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origins")]
+async fn origin_summaries(
+    path: web::Path<String>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let carrier = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+
+    match repo.find_origin_summaries_by_carrier_code(&carrier.code).await {
+        Ok(summaries) => HttpResponse::Ok().json(with_airport_names(&airports, summaries)),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch origins").error_response()
+        }
+    }
+}
+
+/// Handler for listing the destinations a carrier has scraped reward flights to from an origin
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - The origin airport or metro area code (e.g., "LHR" or "LON")
+///
+/// # Returns
+/// Every destination in the latest data with its flight count, latest scrape and departure range
+#[utoipa::path(
+    tag = "routes",
+    responses(
+        (status = 200, description = "Every destination from the origin", body = Vec<AvailabilitySummary>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/origin/{origin}/destinations")]
+async fn destination_summaries(
+    path: web::Path<(String, String)>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let (carrier, origin) = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let origins = match expand_airport_codes(&airports, &[origin]) {
+        Ok(codes) => codes,
+        Err(message) => return Problem::invalid_field("origin", message).error_response(),
+    };
+
+    match repo.find_destination_summaries_by_origins_and_carrier_code(&origins, &carrier.code).await {
+        Ok(summaries) => HttpResponse::Ok().json(with_airport_names(&airports, summaries)),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch destinations").error_response()
+        }
+    }
+}
+
+/// Handler for listing the saved searches
+///
+/// # Returns
+/// Every saved search ordered by id
+#[utoipa::path(
+    tag = "saved-searches",
+    responses(
+        (status = 200, description = "Every saved search", body = Vec<SavedSearch>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/saved-searches")]
+async fn list_saved_searches(
+    watchlist: web::Data<dyn WatchlistRepository>,
+) -> impl Responder {
+    match watchlist.find_all_saved_searches().await {
+        Ok(saved_searches) => HttpResponse::Ok().json(saved_searches),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch saved searches").error_response()
+        }
+    }
+}
+
+/// Handler for creating a saved search
+///
+/// # Parameters
+/// * `body` - The saved search: name, carrier_code, origin, destination, departure_from,
+///   departure_to, cabin_type, min_seats (default: 1), max_points, and optionally a
+///   webhook_url with the webhook_secret its payloads are signed with. Webhook URLs must
+///   name a publicly routable host, not a loopback, private or link-local one
+///
+/// # Returns
+/// The created saved search with a 201 Created status
+#[utoipa::path(
+    tag = "saved-searches",
+    responses(
+        (status = 201, description = "The created saved search", body = SavedSearch),
+        ProblemResponses
+    )
+)]
+#[post("/api/v1/saved-searches")]
+async fn create_saved_search(
+    body: web::Json<SavedSearchRequest>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+    notifier: web::Data<WebhookNotifier>,
+) -> impl Responder {
+    let request = match body.into_inner().validate(&carriers, &airports, notifier.config()) {
+        Ok(request) => request,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    match watchlist.create_saved_search(&request).await {
+        Ok(saved_search) => HttpResponse::Created().json(saved_search),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to create saved search").error_response()
+        }
+    }
+}
+
+/// Handler for retrieving a saved search
+///
+/// # Parameters
+/// * `id` - The saved search id
+///
+/// # Returns
+/// The saved search, or 404 if it does not exist
+#[utoipa::path(
+    tag = "saved-searches",
+    responses(
+        (status = 200, description = "The saved search", body = SavedSearch),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/saved-searches/{id}")]
+async fn get_saved_search(
+    path: web::Path<i64>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match watchlist.find_saved_search_by_id(id).await {
+        Ok(Some(saved_search)) => HttpResponse::Ok().json(saved_search),
+        Ok(None) => Problem::not_found(format!("Saved search {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch saved search").error_response()
+        }
+    }
+}
+
+/// Handler for replacing a saved search
+///
+/// # Parameters
+/// * `id` - The saved search id
+/// * `body` - The saved search, as for creating one
+///
+/// # Returns
+/// The updated saved search, or 404 if it does not exist
+#[utoipa::path(
+    tag = "saved-searches",
+    responses(
+        (status = 200, description = "The updated saved search", body = SavedSearch),
+        ProblemResponses
+    )
+)]
+#[put("/api/v1/saved-searches/{id}")]
+async fn update_saved_search(
+    path: web::Path<i64>,
+    body: web::Json<SavedSearchRequest>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+    notifier: web::Data<WebhookNotifier>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let request = match body.into_inner().validate(&carriers, &airports, notifier.config()) {
+        Ok(request) => request,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    match watchlist.update_saved_search(id, &request).await {
+        Ok(Some(saved_search)) => HttpResponse::Ok().json(saved_search),
+        Ok(None) => Problem::not_found(format!("Saved search {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to update saved search").error_response()
+        }
+    }
+}
+
+/// Handler for deleting a saved search
+///
+/// # Parameters
+/// * `id` - The saved search id
+///
+/// # Returns
+/// 204 No Content, or 404 if the saved search does not exist
+#[utoipa::path(
+    tag = "saved-searches",
+    responses(
+        (status = 204, description = "The saved search was deleted"),
+        ProblemResponses
+    )
+)]
+#[delete("/api/v1/saved-searches/{id}")]
+async fn delete_saved_search(
+    path: web::Path<i64>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    match watchlist.delete_saved_search(id).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => Problem::not_found(format!("Saved search {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to delete saved search").error_response()
+        }
+    }
+}
+
+/// Handler for evaluating a saved search against the latest reward flights
+///
+/// # Parameters
+/// * `id` - The saved search id
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+///
+/// # Returns
+/// A paginated list of the latest reward flights matching the saved search, or 404 if it does not exist
+#[utoipa::path(
+    tag = "saved-searches",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "A page of flights matching the saved search", body = Page<RewardFlightLatest>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/saved-searches/{id}/matches")]
+async fn saved_search_matches(
+    path: web::Path<i64>,
+    query: web::Query<PaginationParams>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    let saved_search = match watchlist.find_saved_search_by_id(id).await {
+        Ok(Some(saved_search)) => saved_search,
+        Ok(None) => return Problem::not_found(format!("Saved search {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch saved search").error_response();
+        }
+    };
+
+    let city_pairs = match saved_search.city_pairs(&airports) {
+        Ok(city_pairs) => city_pairs,
+        Err(message) => return Problem::bad_request(message).error_response(),
+    };
+
+    match repo.find_by_city_pairs_and_carrier_code_and_departure_between(
+        &city_pairs,
+        &saved_search.carrier_code,
+        saved_search.departure_from,
+        saved_search.departure_to,
+        &saved_search.filter(),
+        page_number,
+        page_size,
+    ).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to evaluate saved search").error_response()
+        }
+    }
+}
+
+/// Handler for sending a saved search's new matches to its webhook immediately
+///
+/// # Parameters
+/// * `id` - The saved search id
+///
+/// # Returns
+/// The logged delivery, 204 No Content when there was nothing new to send, or 404 if the saved search does not exist
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The logged delivery", body = WebhookDelivery),
+        (status = 204, description = "There were no new matches to send"),
+        ProblemResponses
+    )
+)]
+#[post("/api/v1/saved-searches/{id}/notify")]
+async fn notify_saved_search(
+    path: web::Path<i64>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    notifier: web::Data<WebhookNotifier>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let saved_search = match watchlist.find_saved_search_by_id(id).await {
+        Ok(Some(saved_search)) => saved_search,
+        Ok(None) => return Problem::not_found(format!("Saved search {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch saved search").error_response();
+        }
+    };
+    if saved_search.webhook_url.is_none() {
+        return Problem::bad_request(format!("Saved search {} has no webhook_url", id)).error_response();
+    }
+
+    match notifier.notify(&saved_search).await {
+        Ok(Some(delivery)) => HttpResponse::Ok().json(delivery),
+        Ok(None) => HttpResponse::NoContent().finish(),
+        Err(message) => {
+            log::error!("Failed to notify saved search {}: {}", id, message);
+            Problem::internal_server_error("Failed to notify saved search").error_response()
+        }
+    }
+}
+
+/// Handler for the webhook delivery log of a saved search
+///
+/// # Parameters
+/// * `id` - The saved search id
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+///
+/// # Returns
+/// A paginated list of the saved search's webhook deliveries, newest first
+#[utoipa::path(
+    tag = "webhooks",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "A page of the saved search's webhook deliveries", body = Page<WebhookDelivery>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/saved-searches/{id}/deliveries")]
+async fn saved_search_deliveries(
+    path: web::Path<i64>,
+    query: web::Query<PaginationParams>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    match watchlist.find_webhook_deliveries_by_saved_search_id(id, page_number, page_size).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch webhook deliveries").error_response()
+        }
+    }
+}
+
+/// Handler for the webhook deliveries that failed every attempt
+///
+/// # Parameters
+/// * `page-number` - The page number for pagination (default: 0)
+/// * `page-size` - The number of items per page (default: 10)
+///
+/// # Returns
+/// A paginated list of dead-lettered webhook deliveries, newest first
+#[utoipa::path(
+    tag = "webhooks",
+    params(PaginationParams),
+    responses(
+        (status = 200, description = "A page of webhook payloads that could not be delivered", body = Page<WebhookDeadLetter>),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/webhook-dead-letters")]
+async fn webhook_dead_letters(
+    query: web::Query<PaginationParams>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+) -> impl Responder {
+    let (page_number, page_size) = match query.page(10) {
+        Ok(page) => page,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    match watchlist.find_webhook_dead_letters(page_number, page_size).await {
+        Ok(page) => HttpResponse::Ok().json(page),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            Problem::internal_server_error("Failed to fetch webhook dead letters").error_response()
+        }
+    }
+}
+
+/// Handler for sending a dead-lettered webhook payload to its saved search's webhook again
+///
+/// # Parameters
+/// * `id` - The dead letter id
+///
+/// # Returns
+/// The logged delivery, after which the dead letter is removed, or 404 if the dead letter or its saved search does not exist
+#[utoipa::path(
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The logged delivery of the replayed payload", body = WebhookDelivery),
+        ProblemResponses
+    )
+)]
+#[post("/api/v1/webhook-dead-letters/{id}/replay")]
+async fn replay_webhook_dead_letter(
+    path: web::Path<i64>,
+    watchlist: web::Data<dyn WatchlistRepository>,
+    notifier: web::Data<WebhookNotifier>,
+) -> impl Responder {
+    let id = path.into_inner();
+
+    let dead_letter = match watchlist.find_webhook_dead_letter_by_id(id).await {
+        Ok(Some(dead_letter)) => dead_letter,
+        Ok(None) => return Problem::not_found(format!("Webhook dead letter {} not found", id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch webhook dead letter").error_response();
+        }
+    };
+    let saved_search = match watchlist.find_saved_search_by_id(dead_letter.saved_search_id).await {
+        Ok(Some(saved_search)) => saved_search,
+        Ok(None) => return Problem::not_found(format!("Saved search {} not found", dead_letter.saved_search_id)).error_response(),
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to fetch saved search").error_response();
+        }
+    };
+    if saved_search.webhook_url.is_none() {
+        return Problem::bad_request(format!("Saved search {} has no webhook_url", saved_search.id)).error_response();
+    }
+
+    match notifier.replay(&dead_letter, &saved_search).await {
+        Ok(delivery) => HttpResponse::Ok().json(delivery),
+        Err(message) => {
+            log::error!("Failed to replay webhook dead letter {}: {}", id, message);
+            Problem::internal_server_error("Failed to replay webhook dead letter").error_response()
+        }
+    }
+}
+
+/// Handler for exporting the latest reward flights of a carrier as newline-delimited JSON
+///
+/// Flights are streamed from the database row by row rather than collected into a page, so a
+/// whole route, or every flight of the carrier, can be exported in constant memory.
+///
+/// # Parameters
+/// * `carrier` - The carrier code (e.g., "vs"), which must be known to the carrier registry
+/// * `origin` - Only export flights from this airport or metro area code (default: every origin)
+/// * `destination` - Only export flights to this airport or metro area code (default: every destination)
+/// * `from` - Only export departures on or after this date, in YYYY-MM-DD format
+/// * `to` - Only export departures on or before this date, in YYYY-MM-DD format
+///
+/// # Returns
+/// An `application/x-ndjson` stream of reward flights, one JSON object per line, ordered by
+/// origin, destination and departure
+#[utoipa::path(
+    tag = "reward-flights",
+    params(BulkExportParams),
+    responses(
+        (status = 200, description = "The latest reward flights, one JSON object per line", body = RewardFlightLatest, content_type = "application/x-ndjson"),
+        ProblemResponses
+    )
+)]
+#[get("/api/v1/airline/{carrier}/reward-flights/export")]
+async fn export_reward_flights(
+    path: web::Path<String>,
+    query: web::Query<BulkExportParams>,
+    repo: web::Data<dyn RewardFlightRepository>,
+    carriers: web::Data<CarrierRegistry>,
+    airports: web::Data<AirportRegistry>,
+) -> impl Responder {
+    let carrier = path.into_inner();
+    let carrier = match carriers.find(&carrier) {
+        Some(carrier) => carrier,
+        None => return Problem::not_found(format!("Unknown carrier '{}'", carrier)).error_response(),
+    };
+    let scope = match query.scope(&airports) {
+        Ok(scope) => scope,
+        Err(error) => return Problem::from(error).error_response(),
+    };
+
+    // Wait for the first row so that a failing query can still be reported as a problem
+    let mut flights = repo.stream_by_carrier_code(&carrier.code, &scope);
+    let first = match flights.try_next().await {
+        Ok(first) => first,
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            return Problem::internal_server_error("Failed to export reward flights").error_response();
+        }
+    };
+
+    let lines = futures_util::stream::iter(first.map(Ok)).chain(flights).map(|flight| {
+        let flight = flight.map_err(|e| {
+            log::error!("Database error: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to export reward flights")
+        })?;
+        ndjson_line(&flight).map_err(|e| {
+            log::error!("Failed to write NDJSON: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to export reward flights")
+        })
+    });
+
+    HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(lines)
+}
+
+/// Scores each flight's cabin prices against the route's recent price history
+async fn attach_deal_scores(
+    repo: &dyn RewardFlightRepository,
+    carrier_code: &str,
+    mut flights: Vec<&mut RewardFlightLatest>,
+) -> Result<(), sqlx::Error> {
+    let current: Vec<RewardFlightLatest> = flights.iter().map(|flight| (**flight).clone()).collect();
+    let scores = repo.find_deal_scores_by_carrier_code(carrier_code, &current, DEAL_LOOKBACK_DAYS).await?;
+    for (flight, deal_scores) in flights.iter_mut().zip(scores) {
+        flight.deal_scores = Some(deal_scores);
+    }
+    Ok(())
+}
+
+/// Writes search results as CSV rows, preceded by the header row when `with_headers` is set
+fn csv_chunk<T>(rows: &[T], with_headers: bool) -> Result<web::Bytes, csv::Error>
+where
+    for<'a> RewardFlightCsvRow: From<&'a T>,
+{
+    let mut writer = csv::WriterBuilder::new().has_headers(false).from_writer(Vec::new());
+    if with_headers {
+        writer.write_record(RewardFlightCsvRow::HEADERS)?;
+    }
+    for row in rows {
+        writer.serialize(RewardFlightCsvRow::from(row))?;
+    }
+    let bytes = writer.into_inner().map_err(|e| csv::Error::from(e.into_error()))?;
+    Ok(web::Bytes::from(bytes))
+}
+
+/// Streams search results as a CSV attachment, in chunks of `EXPORT_CHUNK_SIZE` rows
+///
+/// The first row is read before responding so that errors running the query can still be
+/// reported as a problem; later errors abort the response.
+async fn csv_export<T>(
+    filename: &str,
+    mut rows: BoxStream<'static, Result<T, sqlx::Error>>,
+) -> Result<HttpResponse, sqlx::Error>
+where
+    T: Send + 'static,
+    for<'a> RewardFlightCsvRow: From<&'a T>,
+{
+    let first_row = rows.try_next().await?;
+
+    let write = |rows: &[T], with_headers: bool| {
+        csv_chunk(rows, with_headers).map_err(|e| {
+            log::error!("Failed to write CSV: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to export reward flights")
+        })
+    };
+    let header = futures_util::stream::once(async move { write(&[], true) });
+    let chunks = futures_util::stream::iter(first_row.map(Ok))
+        .chain(rows)
+        .try_chunks(EXPORT_CHUNK_SIZE)
+        .map(move |chunk| match chunk {
+            Ok(rows) => write(&rows, false),
+            Err(e) => {
+                log::error!("Database error: {}", e.1);
+                Err(actix_web::error::ErrorInternalServerError("Failed to export reward flights"))
+            }
+        });
+    let stream = header.chain(chunks);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+        .streaming(stream))
+}
+
+/// Streams the rows of an already fetched page, for exports of a single page
+fn page_rows<T: Send + 'static>(page: Page<T>) -> BoxStream<'static, Result<T, sqlx::Error>> {
+    futures_util::stream::iter(page.content.into_iter().map(Ok)).boxed()
+}
+
+/// Serialises a value as one line of newline-delimited JSON
+fn ndjson_line<T: Serialize>(value: &T) -> Result<web::Bytes, serde_json::Error> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    Ok(web::Bytes::from(line))
+}
+
+/// Names each airport summary after the airport in the reference data, when known
+fn with_airport_names(airports: &AirportRegistry, summaries: Vec<AvailabilitySummary>) -> Vec<AvailabilitySummary> {
+    summaries
+        .into_iter()
+        .map(|summary| AvailabilitySummary {
+            name: airports.find(&summary.code).map(|airport| airport.name.clone()),
+            ..summary
+        })
+        .collect()
+}
+
+// Largest page of results a request may ask for
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Validates a zero-based page number and a page size of 1 to `MAX_PAGE_SIZE`, defaulting to the first page of `default_page_size`
+fn validate_page(page_number: Option<u32>, page_size: Option<u32>, default_page_size: u32) -> Result<(usize, usize), FieldError> {
+    let page_size = page_size.unwrap_or(default_page_size);
+    if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        return Err(FieldError::new("page-size", format!("Invalid page-size. Expected between 1 and {}", MAX_PAGE_SIZE)));
+    }
+    Ok((page_number.unwrap_or(0) as usize, page_size as usize))
+}
+
+// Query parameters shared by the search endpoints: pagination and result filters
+#[derive(Debug, Deserialize, IntoParams)]
+struct SearchParams {
+    #[serde(rename = "page-number")]
+    page_number: Option<u32>,
+    #[serde(rename = "page-size")]
+    page_size: Option<u32>,
+    cabin: Option<CabinType>,
+    #[serde(rename = "min-seats", alias = "passengers")]
+    min_seats: Option<i32>,
+    #[serde(rename = "saver-only")]
+    saver_only: Option<bool>,
+    #[serde(rename = "min-points")]
+    min_points: Option<i32>,
+    #[serde(rename = "max-points")]
+    max_points: Option<i32>,
+    weekdays: Option<String>,
+    #[serde(rename = "weekends-only")]
+    weekends_only: Option<bool>,
+    #[serde(rename = "deal-score")]
+    deal_score: Option<bool>,
+}
+
+impl SearchParams {
+    /// Validates the page-number and page-size parameters, defaulting to the first page of `default_page_size`
+    fn page(&self, default_page_size: u32) -> Result<(usize, usize), FieldError> {
+        validate_page(self.page_number, self.page_size, default_page_size)
+    }
+
+    /// Validates the filter parameters into a `SearchFilter`
+    fn filter(&self) -> Result<SearchFilter, FieldError> {
+        if self.min_seats.is_some_and(|min_seats| min_seats < 1) {
+            return Err(FieldError::new("min-seats", "Invalid min-seats. Expected a positive number of seats"));
+        }
+        if self.min_points.is_some_and(|min_points| min_points < 0) || self.max_points.is_some_and(|max_points| max_points < 0) {
+            let field = if self.min_points.is_some_and(|min_points| min_points < 0) { "min-points" } else { "max-points" };
+            return Err(FieldError::new(field, "Invalid points range. Expected min-points and max-points to be positive"));
+        }
+        if let (Some(min_points), Some(max_points)) = (self.min_points, self.max_points)
+            && min_points > max_points
+        {
+            return Err(FieldError::new("min-points", "Invalid points range. Expected min-points <= max-points"));
+        }
+
+        // Allowed departure days, where weekends-only narrows them to Saturday and Sunday
+        let mut weekdays = match &self.weekdays {
+            Some(weekdays) => {
+                let mut parsed = Vec::new();
+                for weekday in weekdays.split(',').map(str::trim).filter(|weekday| !weekday.is_empty()) {
+                    match weekday.parse::<Weekday>() {
+                        Ok(weekday) => parsed.push(weekday),
+                        Err(_) => {
+                            return Err(FieldError::new(
+                                "weekdays",
+                                format!("Invalid weekday '{}'. Expected MON, TUE, WED, THU, FRI, SAT or SUN", weekday),
+                            ))
+                        },
+                    }
+                }
+                Some(parsed)
+            },
+            None => None,
+        };
+        if self.weekends_only.unwrap_or(false) {
+            let weekend = [Weekday::Sat, Weekday::Sun];
+            weekdays = Some(match weekdays {
+                Some(weekdays) => weekdays.into_iter().filter(|weekday| weekend.contains(weekday)).collect(),
+                None => weekend.to_vec(),
+            });
+        }
+        if weekdays.as_ref().is_some_and(|weekdays| weekdays.is_empty()) {
+            return Err(FieldError::new("weekdays", "Invalid weekdays. Expected at least one departure day"));
+        }
+
+        Ok(SearchFilter {
+            cabin_type: self.cabin,
+            min_seats: self.min_seats,
+            saver_only: self.saver_only.unwrap_or(false),
+            min_points: self.min_points,
+            max_points: self.max_points,
+            weekdays,
+        })
+    }
+}
+
+// A validated search of the latest reward flights on a route and departure window
+#[derive(Clone)]
+struct LatestSearch {
+    carrier_code: String,
+    origins: Vec<String>,
+    destinations: Vec<String>,
+    from_date: NaiveDate,
+    to_date: NaiveDate,
+    filter: SearchFilter,
+    page_number: usize,
+    page_size: usize,
+    deal_score: bool,
+}
+
+impl LatestSearch {
+    /// Validates the route, dates and search parameters, expanding metro area codes to their airports
+    fn new(
+        carrier: &Carrier,
+        origin: &str,
+        destination: &str,
+        from: &str,
+        to: &str,
+        params: &SearchParams,
+        airports: &AirportRegistry,
+    ) -> Result<Self, FieldError> {
+        let origins = expand_airport_codes(airports, &[origin.to_string()]).map_err(|message| FieldError::new("origin", message))?;
+        let destinations = expand_airport_codes(airports, &[destination.to_string()])
+            .map_err(|message| FieldError::new("destination", message))?;
+        let filter = params.filter()?;
+        let (page_number, page_size) = params.page(10)?;
+
+        // Parse dates
+        let from_date = NaiveDate::parse_from_str(from, "%Y-%m-%d")
+            .map_err(|_| FieldError::new("from", "Invalid 'from' date format. Expected YYYY-MM-DD"))?;
+        let to_date = NaiveDate::parse_from_str(to, "%Y-%m-%d")
+            .map_err(|_| FieldError::new("to", "Invalid 'to' date format. Expected YYYY-MM-DD"))?;
+
+        Ok(Self {
+            carrier_code: carrier.code.clone(),
+            origins,
+            destinations,
+            from_date,
+            to_date,
+            filter,
+            page_number,
+            page_size,
+            deal_score: params.deal_score.unwrap_or(false),
+        })
+    }
+
+    /// Runs the search, scoring each flight's prices when deal scores were requested
+    async fn find(&self, repo: &dyn RewardFlightRepository) -> Result<Page<RewardFlightLatest>, sqlx::Error> {
+        // Query the repository, searching every airport pair at once for metro areas
+        let mut page = repo.find_by_city_pairs_and_carrier_code_and_departure_between(
+            &city_pairs(&self.origins, &self.destinations),
+            &self.carrier_code,
+            self.from_date,
+            self.to_date,
+            &self.filter,
+            self.page_number,
+            self.page_size,
+        ).await?;
+
+        if self.deal_score {
+            attach_deal_scores(repo, &self.carrier_code, page.content.iter_mut().collect()).await?;
+        }
+        Ok(page)
+    }
+
+    /// Runs the search across all of its pages, scoring each flight's prices when deal scores were requested
+    async fn find_all(&self, repo: &dyn RewardFlightRepository) -> Result<Vec<RewardFlightLatest>, sqlx::Error> {
+        let mut flights: Vec<RewardFlightLatest> = self.stream(repo).try_collect().await?;
+        if self.deal_score {
+            attach_deal_scores(repo, &self.carrier_code, flights.iter_mut().collect()).await?;
+        }
+        Ok(flights)
+    }
+
+    /// Whether a scraped departure is on one of the search's routes and within its dates
+    fn covers(&self, departure: &ScrapedDeparture) -> bool {
+        departure.carrier_code == self.carrier_code
+            && self.origins.contains(&departure.origin)
+            && self.destinations.contains(&departure.destination)
+            && (self.from_date..=self.to_date).contains(&departure.departure)
+    }
+
+    /// Whether a flight departs within the search's dates
+    fn departs_within(&self, flight: &RewardFlightLatest) -> bool {
+        flight.departure.parse::<NaiveDate>().is_ok_and(|departure| (self.from_date..=self.to_date).contains(&departure))
+    }
+
+    /// The same search, narrowed to the departures from `from_date` to `to_date`
+    fn departing_between(&self, from_date: NaiveDate, to_date: NaiveDate) -> Self {
+        Self { from_date, to_date, ..self.clone() }
+    }
+
+    /// Streams every flight of the search, across all of its pages and without deal scores
+    fn stream(&self, repo: &dyn RewardFlightRepository) -> BoxStream<'static, Result<RewardFlightLatest, sqlx::Error>> {
+        repo.stream_by_city_pairs_and_carrier_code_and_departure_between(
+            &city_pairs(&self.origins, &self.destinations),
+            &self.carrier_code,
+            self.from_date,
+            self.to_date,
+            &self.filter,
+        )
+    }
+}
+
+// A live search definition: the route and dates of the latest search, with its query parameters
+#[derive(Debug, Deserialize)]
+struct LiveSearchRequest {
+    origin: String,
+    destination: String,
+    from: String,
+    to: String,
+    #[serde(flatten)]
+    params: SearchParams,
+}
+
+impl LiveSearchRequest {
+    fn search(&self, carrier: &Carrier, airports: &AirportRegistry) -> Result<LatestSearch, FieldError> {
+        LatestSearch::new(carrier, &self.origin, &self.destination, &self.from, &self.to, &self.params, airports)
+    }
+}
+
+// Most flights a live search follows, as every scrape touching its departures diffs them again
+const MAX_LIVE_SEARCH_RESULTS: i64 = 2000;
+
+// Query parameters for the availability calendar: the search filters, as its days are neither paginated nor scored
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(deny_unknown_fields)]
+struct CalendarParams {
+    cabin: Option<CabinType>,
+    #[serde(rename = "min-seats", alias = "passengers")]
+    min_seats: Option<i32>,
+    #[serde(rename = "saver-only")]
+    saver_only: Option<bool>,
+    #[serde(rename = "min-points")]
+    min_points: Option<i32>,
+    #[serde(rename = "max-points")]
+    max_points: Option<i32>,
+    weekdays: Option<String>,
+    #[serde(rename = "weekends-only")]
+    weekends_only: Option<bool>,
+}
+
+impl CalendarParams {
+    /// Validates the filter parameters into a `SearchFilter`, as for the other searches
+    fn filter(&self) -> Result<SearchFilter, FieldError> {
+        SearchParams {
+            page_number: None,
+            page_size: None,
+            cabin: self.cabin,
+            min_seats: self.min_seats,
+            saver_only: self.saver_only,
+            min_points: self.min_points,
+            max_points: self.max_points,
+            weekdays: self.weekdays.clone(),
+            weekends_only: self.weekends_only,
+            deal_score: None,
+        }
+        .filter()
+    }
+}
+
+// Query parameters of endpoints that only paginate
+#[derive(Debug, Deserialize, IntoParams)]
+struct PaginationParams {
+    #[serde(rename = "page-number")]
+    page_number: Option<u32>,
+    #[serde(rename = "page-size")]
+    page_size: Option<u32>,
+}
+
+impl PaginationParams {
+    /// Validates the page-number and page-size parameters, defaulting to the first page of `default_page_size`
+    fn page(&self, default_page_size: u32) -> Result<(usize, usize), FieldError> {
+        validate_page(self.page_number, self.page_size, default_page_size)
+    }
+}
+
+// Query parameters for exporting search results
+#[derive(Debug, Deserialize, IntoParams)]
+struct ExportParams {
+    format: Option<ExportFormat>,
+    all: Option<bool>,
+}
+
+impl ExportParams {
+    /// The requested format, from `format` or else the `Accept` header, defaulting to JSON
+    fn format(&self, req: &HttpRequest) -> Result<ExportFormat, FieldError> {
+        let format = self.format.unwrap_or_else(|| {
+            let accept = req.headers().get("Accept").and_then(|accept| accept.to_str().ok()).unwrap_or_default();
+            if accept.contains("text/csv") { ExportFormat::Csv } else { ExportFormat::Json }
+        });
+        if self.all() && format == ExportFormat::Json {
+            return Err(FieldError::new("all", "Invalid all. Exporting every result is only supported as CSV"));
+        }
+        Ok(format)
+    }
+
+    fn all(&self) -> bool {
+        self.all.unwrap_or(false)
+    }
+}
+
+// Number of rows written to each chunk of a streamed CSV export
+const EXPORT_CHUNK_SIZE: usize = 500;
+
+// Query parameters for the bulk export
+#[derive(Debug, Deserialize, IntoParams)]
+struct BulkExportParams {
+    origin: Option<String>,
+    destination: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+impl BulkExportParams {
+    /// Validates the parameters into an `ExportScope`, expanding metro area codes to their airports
+    fn scope(&self, airports: &AirportRegistry) -> Result<ExportScope, FieldError> {
+        let expand = |field: &str, code: &Option<String>| {
+            code.as_ref()
+                .map(|code| expand_airport_codes(airports, std::slice::from_ref(code)))
+                .transpose()
+                .map_err(|message| FieldError::new(field, message))
+        };
+        let parse_date = |field: &str, date: &Option<String>| {
+            date.as_deref()
+                .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+                .transpose()
+                .map_err(|_| FieldError::new(field, format!("Invalid '{}' date format. Expected YYYY-MM-DD", field)))
+        };
+
+        Ok(ExportScope {
+            origins: expand("origin", &self.origin)?,
+            destinations: expand("destination", &self.destination)?,
+            from_date: parse_date("from", &self.from)?,
+            to_date: parse_date("to", &self.to)?,
+        })
+    }
+}
+
+// Query parameters for the explore search
+#[derive(Debug, Deserialize, IntoParams)]
+struct ExploreParams {
+    sort: Option<ExploreSort>,
+}
+
+// Query parameters for the flexible date search
+#[derive(Debug, Deserialize, IntoParams)]
+struct FlexibleParams {
+    #[serde(rename = "flex-days")]
+    flex_days: Option<u32>,
+}
+
+// Query parameters for the availability change feed
+#[derive(Debug, Deserialize, IntoParams)]
+struct ChangeParams {
+    #[serde(rename = "page-number")]
+    page_number: Option<u32>,
+    #[serde(rename = "page-size")]
+    page_size: Option<u32>,
+    cabin: Option<CabinType>,
+    #[serde(rename = "event-types")]
+    event_types: Option<String>,
+    since: Option<DateTime<Utc>>,
+}
+
+impl ChangeParams {
+    /// Validates the page-number and page-size parameters, defaulting to the first page of `default_page_size`
+    fn page(&self, default_page_size: u32) -> Result<(usize, usize), FieldError> {
+        validate_page(self.page_number, self.page_size, default_page_size)
+    }
+
+    /// Validates the filter parameters into a `ChangeFilter`
+    fn filter(&self) -> Result<ChangeFilter, FieldError> {
+        Ok(ChangeFilter {
+            cabin_type: self.cabin,
+            event_types: parse_event_types(self.event_types.as_deref())?,
+            since: self.since,
+        })
+    }
+}
+
+// Query parameters for the availability change stream
+#[derive(Debug, Deserialize, IntoParams)]
+struct ChangeStreamParams {
+    origin: Option<String>,
+    destination: Option<String>,
+    cabin: Option<CabinType>,
+    #[serde(rename = "event-types")]
+    event_types: Option<String>,
+}
+
+/// Parses a comma-separated list of change event types
+fn parse_event_types(event_types: Option<&str>) -> Result<Option<Vec<ChangeEventType>>, FieldError> {
+    let Some(event_types) = event_types else {
+        return Ok(None);
+    };
+    let mut parsed = Vec::new();
+    for event_type in event_types.split(',').map(str::trim).filter(|event_type| !event_type.is_empty()) {
+        parsed.push(event_type.parse::<ChangeEventType>().map_err(|message| FieldError::new("event-types", message))?);
+    }
+    if parsed.is_empty() {
+        return Err(FieldError::new("event-types", "Invalid event-types. Expected at least one event type"));
+    }
+    Ok(Some(parsed))
+}
+
+// Interval between keep-alive comments on an idle change stream
+const CHANGE_STREAM_KEEP_ALIVE: std::time::Duration = std::time::Duration::from_secs(15);
+
+// Days of price history the deal scores compare against
+const DEAL_LOOKBACK_DAYS: u32 = 90;
+
+// Query parameters for the route statistics and release pattern
+#[derive(Debug, Deserialize, IntoParams)]
+struct StatisticsParams {
+    #[serde(rename = "lookback-days")]
+    lookback_days: Option<u32>,
+}
+
+// Default and largest lookback windows of the route statistics and release pattern, in days
+const DEFAULT_LOOKBACK_DAYS: u32 = 90;
+const MAX_LOOKBACK_DAYS: u32 = 365;
+
+// Query parameters for the price history
+#[derive(Debug, Deserialize, IntoParams)]
+struct PriceHistoryParams {
+    cabin: Option<CabinType>,
+    interval: Option<HistoryInterval>,
+}
+
+// Largest window either side of the target date for the flexible date search
+const MAX_FLEX_DAYS: u32 = 14;
+
+// Query parameters for the round trip search
+#[derive(Debug, Deserialize, IntoParams)]
+struct RoundTripParams {
+    #[serde(rename = "outbound-from")]
+    outbound_from: String,
+    #[serde(rename = "outbound-to")]
+    outbound_to: String,
+    #[serde(rename = "return-from")]
+    return_from: String,
+    #[serde(rename = "return-to")]
+    return_to: String,
+    #[serde(rename = "return-cabin")]
+    return_cabin: Option<String>,
+    #[serde(rename = "min-trip-days")]
+    min_trip_days: Option<i32>,
+    #[serde(rename = "max-trip-days")]
+    max_trip_days: Option<i32>,
+    #[serde(rename = "return-origins")]
+    return_origins: Option<String>,
+    #[serde(rename = "return-destinations")]
+    return_destinations: Option<String>,
+}
+
+// Upper bound on the routes searched in each direction by a single request
+const MAX_CITY_PAIRS: usize = 25;
+
+/// Splits a comma-separated list of airport codes, falling back to `default` when absent or empty
+fn split_airport_codes(codes: Option<&str>, default: &str) -> Vec<String> {
+    let codes: Vec<String> = codes
+        .unwrap_or_default()
+        .split(',')
+        .map(|code| code.trim().to_string())
+        .filter(|code| !code.is_empty())
+        .collect();
+    if codes.is_empty() {
+        vec![default.to_string()]
+    } else {
+        codes
+    }
+}
+
+/// Resolves a path parameter to a single known airport code
+fn resolve_airport_code(airports: &AirportRegistry, code: &str) -> Result<String, String> {
+    match airports.find(code) {
+        Some(airport) => Ok(airport.code.clone()),
+        None if !airports.metro_airports(code).is_empty() => {
+            Err(format!("Metro area code '{}' is not supported here. Expected an airport code", code))
+        },
+        None => Err(format!("Unknown airport code '{}'", code)),
+    }
+}
+
+/// Expands airport and metro area codes to the concrete airports they cover
+fn expand_airport_codes(airports: &AirportRegistry, codes: &[String]) -> Result<Vec<String>, String> {
+    let mut expanded: Vec<String> = Vec::new();
+    for code in codes {
+        match airports.expand(code) {
+            Some(airport_codes) => {
+                for airport_code in airport_codes {
+                    if !expanded.contains(&airport_code) {
+                        expanded.push(airport_code);
+                    }
+                }
+            },
+            None => return Err(format!("Unknown airport code '{}'", code)),
+        }
+    }
+    Ok(expanded)
+}
+
+/// Every combination of the given origins and destinations, skipping same-airport pairs
+fn city_pairs(origins: &[String], destinations: &[String]) -> Vec<CityPair> {
+    origins
+        .iter()
+        .flat_map(|origin| {
+            destinations
+                .iter()
+                .filter(move |destination| *destination != origin)
+                .map(move |destination| CityPair::new(origin, destination))
+        })
+        .collect()
+}
+
+// Enum for cabin types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CabinType {
+    Economy,
+    PremiumEconomy,
+    Business,
+    First,
+}
+
+impl CabinType {
+    pub const ALL: [CabinType; 4] = [
+        CabinType::Economy,
+        CabinType::PremiumEconomy,
+        CabinType::Business,
+        CabinType::First,
+    ];
+
+    /// The cabin name as used in paths and in the repository queries
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CabinType::Economy => "ECONOMY",
+            CabinType::PremiumEconomy => "PREMIUM_ECONOMY",
+            CabinType::Business => "BUSINESS",
+            CabinType::First => "FIRST",
+        }
+    }
+
+    /// The alias of the cabin's award table in the repository queries
+    pub fn award_alias(&self) -> &'static str {
+        match self {
+            CabinType::Economy => "ae",
+            CabinType::PremiumEconomy => "ape",
+            CabinType::Business => "ab",
+            CabinType::First => "af",
+        }
+    }
+
+    /// The cabin's award table; its history table carries a `_history` suffix
+    pub fn award_table(&self) -> &'static str {
+        match self {
+            CabinType::Economy => "award_economy",
+            CabinType::PremiumEconomy => "award_premium_economy",
+            CabinType::Business => "award_business",
+            CabinType::First => "award_first",
+        }
+    }
+
+    /// The points column of the cabin's award table
+    pub fn points_column(&self) -> String {
+        format!("{}.cabin_points_value", self.award_alias())
+    }
+
+    /// SQL condition matching flights with bookable award seats in this cabin
+    pub fn availability_condition(&self) -> String {
+        format!(
+            "{alias}.cabin_points_value IS NOT NULL AND {alias}.cabin_class_seat_count > 0",
+            alias = self.award_alias()
+        )
+    }
+}
+
+impl std::str::FromStr for CabinType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ECONOMY" => Ok(CabinType::Economy),
+            "PREMIUM_ECONOMY" => Ok(CabinType::PremiumEconomy),
+            "BUSINESS" => Ok(CabinType::Business),
+            "FIRST" => Ok(CabinType::First),
+            _ => Err("Invalid cabin type. Expected ECONOMY, PREMIUM_ECONOMY, BUSINESS, or FIRST".to_string()),
+        }
+    }
+}
+
+// Format of the search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+// Sort order for the explore search
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExploreSort {
+    #[default]
+    Points,
+    Departure,
+}
+
+impl ExploreSort {
+    /// ORDER BY clause over the per-destination `cheapest` subquery
+    fn order_by_clause(&self) -> &'static str {
+        match self {
+            ExploreSort::Points => "cheapest.selected_cabin_points_value ASC, cheapest.departure ASC, cheapest.destination ASC",
+            ExploreSort::Departure => "cheapest.departure ASC, cheapest.selected_cabin_points_value ASC, cheapest.destination ASC",
+        }
+    }
+}
+
+// Kinds of availability change between consecutive scrapes of a flight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChangeEventType {
+    SeatsOpened,
+    SeatsSoldOut,
+    PointsChanged,
+    SaverChanged,
+}
+
+impl ChangeEventType {
+    pub const ALL: [ChangeEventType; 4] = [
+        ChangeEventType::SeatsOpened,
+        ChangeEventType::SeatsSoldOut,
+        ChangeEventType::PointsChanged,
+        ChangeEventType::SaverChanged,
+    ];
+
+    /// The event type name as used in query parameters and the change feed query
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeEventType::SeatsOpened => "SEATS_OPENED",
+            ChangeEventType::SeatsSoldOut => "SEATS_SOLD_OUT",
+            ChangeEventType::PointsChanged => "POINTS_CHANGED",
+            ChangeEventType::SaverChanged => "SAVER_CHANGED",
+        }
+    }
+
+    /// SQL condition over the change feed's `snapshots` columns of the given cabin;
+    /// a missing award counts as no seats
+    fn sql_condition(&self, cabin_type: CabinType) -> String {
+        let alias = cabin_type.award_alias();
+        match self {
+            ChangeEventType::SeatsOpened => format!(
+                "COALESCE({alias}_previous_seats, 0) = 0 AND COALESCE({alias}_seats, 0) > 0"
+            ),
+            ChangeEventType::SeatsSoldOut => format!(
+                "COALESCE({alias}_previous_seats, 0) > 0 AND COALESCE({alias}_seats, 0) = 0"
+            ),
+            ChangeEventType::PointsChanged => format!(
+                "{alias}_previous_points IS NOT NULL AND {alias}_points IS NOT NULL AND {alias}_previous_points <> {alias}_points"
+            ),
+            ChangeEventType::SaverChanged => format!(
+                "{alias}_previous_saver IS NOT NULL AND {alias}_saver IS NOT NULL AND {alias}_previous_saver <> {alias}_saver"
+            ),
+        }
+    }
+
+    /// Whether the change is of this type, matching `sql_condition`
+    pub fn detected_in(&self, change: &AvailabilityChange) -> bool {
+        match self {
+            ChangeEventType::SeatsOpened => change.previous_seats.unwrap_or(0) == 0 && change.seats.unwrap_or(0) > 0,
+            ChangeEventType::SeatsSoldOut => change.previous_seats.unwrap_or(0) > 0 && change.seats.unwrap_or(0) == 0,
+            ChangeEventType::PointsChanged => matches!(
+                (change.previous_points, change.points),
+                (Some(previous), Some(current)) if previous != current
+            ),
+            ChangeEventType::SaverChanged => matches!(
+                (change.previous_saver, change.saver),
+                (Some(previous), Some(current)) if previous != current
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for ChangeEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChangeEventType::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!(
+                "Invalid event type '{}'. Expected SEATS_OPENED, SEATS_SOLD_OUT, POINTS_CHANGED or SAVER_CHANGED", s
+            ))
+    }
+}
+
+// Bucket size of a price history; buckets keep the lowest points, most seats and any saver award
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryInterval {
+    #[default]
+    Raw,
+    Hourly,
+    Daily,
+}
+
+impl HistoryInterval {
+    /// SQL expression bucketing `rfh.scraped_at`
+    fn bucket_expression(&self) -> &'static str {
+        match self {
+            HistoryInterval::Raw => "rfh.scraped_at",
+            HistoryInterval::Hourly => "date_trunc('hour', rfh.scraped_at)",
+            HistoryInterval::Daily => "date_trunc('day', rfh.scraped_at)",
+        }
+    }
+
+    /// The start of the bucket containing `scraped_at`
+    fn truncate(&self, scraped_at: DateTime<Utc>) -> DateTime<Utc> {
+        let bucket = match self {
+            HistoryInterval::Raw => return scraped_at,
+            HistoryInterval::Hourly => chrono::TimeDelta::hours(1),
+            HistoryInterval::Daily => chrono::TimeDelta::days(1),
+        };
+        scraped_at.duration_trunc(bucket).unwrap_or(scraped_at)
+    }
+}
+
+// A carrier whose scraped reward data is served by this API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Carrier {
+    pub code: String,
+    pub display_name: String,
+    pub cabins: Vec<CabinType>,
+}
+
+impl Carrier {
+    pub fn supports_cabin(&self, cabin_type: CabinType) -> bool {
+        self.cabins.contains(&cabin_type)
+    }
+}
+
+/// Registry of the carriers known to the API
+///
+/// Carriers are looked up by their IATA code, case-insensitively, so both
+/// `/airline/vs/...` and `/airline/VS/...` resolve to the same carrier.
+/// The registry is read from the JSON file named by `CARRIER_REGISTRY_PATH`
+/// when set, otherwise the built-in defaults are used.
+pub struct CarrierRegistry {
+    carriers: Vec<Carrier>,
+}
+
+impl CarrierRegistry {
+    pub fn new(carriers: Vec<Carrier>) -> Self {
+        Self { carriers }
+    }
+
+    pub fn from_env() -> std::io::Result<Self> {
+        match std::env::var("CARRIER_REGISTRY_PATH") {
+            Ok(path) => {
+                info!("Loading carrier registry from {}", path);
+                let contents = std::fs::read_to_string(&path)?;
+                let carriers: Vec<Carrier> = serde_json::from_str(&contents)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                Ok(Self::new(carriers))
+            },
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn find(&self, code: &str) -> Option<&Carrier> {
+        self.carriers
+            .iter()
+            .find(|carrier| carrier.code.eq_ignore_ascii_case(code))
+    }
+
+    pub fn carriers(&self) -> &[Carrier] {
+        &self.carriers
+    }
+}
+
+impl Default for CarrierRegistry {
+    fn default() -> Self {
+        Self::new(vec![Carrier {
+            code: "VS".to_string(),
+            display_name: "Virgin Atlantic".to_string(),
+            cabins: CabinType::ALL.to_vec(),
+        }])
+    }
+}
+
+// An airport from the bundled reference dataset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Airport {
+    pub code: String,
+    pub name: String,
+    pub city: String,
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub timezone: String,
+    pub metro_code: Option<String>,
+}
+
+// Airport reference data bundled with the binary
+const BUNDLED_AIRPORTS: &str = include_str!("../data/airports.json");
+
+/// Registry of known airports and the metro areas grouping them
+///
+/// Codes are matched case-insensitively. A metro area code such as `LON`
+/// expands to every airport whose `metro_code` is `LON`. The registry is read
+/// from the JSON file named by `AIRPORT_DATA_PATH` when set, otherwise the
+/// bundled dataset is used.
+pub struct AirportRegistry {
+    airports: Vec<Airport>,
+}
+
+impl AirportRegistry {
+    pub fn new(airports: Vec<Airport>) -> Self {
+        Self { airports }
+    }
+
+    pub fn from_env() -> std::io::Result<Self> {
+        let contents = match std::env::var("AIRPORT_DATA_PATH") {
+            Ok(path) => {
+                info!("Loading airport data from {}", path);
+                std::fs::read_to_string(&path)?
+            },
+            Err(_) => return Ok(Self::default()),
+        };
+        let airports: Vec<Airport> = serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::new(airports))
+    }
+
+    pub fn find(&self, code: &str) -> Option<&Airport> {
+        self.airports
+            .iter()
+            .find(|airport| airport.code.eq_ignore_ascii_case(code))
+    }
+
+    /// The airports of a metro area, empty when `code` is not a metro area code
+    pub fn metro_airports(&self, code: &str) -> Vec<&Airport> {
+        self.airports
+            .iter()
+            .filter(|airport| {
+                airport
+                    .metro_code
+                    .as_deref()
+                    .is_some_and(|metro_code| metro_code.eq_ignore_ascii_case(code))
+            })
+            .collect()
+    }
+
+    /// Expands an airport or metro area code to the concrete airport codes it covers
+    ///
+    /// Airport codes take precedence over metro codes, so a code that is both
+    /// (e.g. `SHA`) resolves to the airport itself. Returns `None` for unknown codes.
+    pub fn expand(&self, code: &str) -> Option<Vec<String>> {
+        if let Some(airport) = self.find(code) {
+            return Some(vec![airport.code.clone()]);
         }
-        
-        // Sort flights by scraped_at (ascending)
-        flights.sort_by(|a, b| a.scraped_at.cmp(&b.scraped_at));
-        
-        // Calculate total elements
-        let total_elements = flights.len() as i64;
-        
-        // Apply pagination
-        let start = page_number * page_size;
-        let end = std::cmp::min(start + page_size, flights.len());
-        let paginated_flights = if start < flights.len() {
-            flights[start..end].to_vec()
+        let metro_airports = self.metro_airports(code);
+        if metro_airports.is_empty() {
+            None
         } else {
-            Vec::new()
+            Some(metro_airports.into_iter().map(|airport| airport.code.clone()).collect())
+        }
+    }
+
+    pub fn airports(&self) -> &[Airport] {
+        &self.airports
+    }
+}
+
+impl Default for AirportRegistry {
+    fn default() -> Self {
+        Self::new(serde_json::from_str(BUNDLED_AIRPORTS).expect("bundled airport data is valid"))
+    }
+}
+
+/// Turns a query string that fails to deserialize into a problem response, naming a malformed page parameter
+fn query_error_handler(err: actix_web::error::QueryPayloadError, req: &HttpRequest) -> actix_web::Error {
+    let detail = match &err {
+        actix_web::error::QueryPayloadError::Deserialize(e) => format!("Invalid query string: {}", e),
+        _ => err.to_string(),
+    };
+    match malformed_page_parameter(req.query_string()) {
+        Some(field) => Problem::invalid_field(field, detail).into(),
+        None => Problem::new(err.status_code(), detail).into(),
+    }
+}
+
+/// The first page-number or page-size parameter of a query string that is not an unsigned number
+fn malformed_page_parameter(query: &str) -> Option<&'static str> {
+    let parameters = web::Query::<Vec<(String, String)>>::from_query(query).ok()?;
+    ["page-number", "page-size"]
+        .into_iter()
+        .find(|field| parameters.iter().any(|(name, value)| name == field && value.parse::<u32>().is_err()))
+}
+
+/// Turns a JSON body that fails to deserialize into a problem response
+fn json_error_handler(err: actix_web::error::JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let detail = match &err {
+        actix_web::error::JsonPayloadError::Deserialize(e) => format!("Invalid JSON body: {}", e),
+        _ => err.to_string(),
+    };
+    Problem::new(err.status_code(), detail).into()
+}
+
+/// Turns a path parameter that fails to deserialize into a problem response
+fn path_error_handler(err: actix_web::error::PathError, _req: &HttpRequest) -> actix_web::Error {
+    let detail = match &err {
+        actix_web::error::PathError::Deserialize(e) => format!("Invalid path: {}", e),
+        _ => err.to_string(),
+    };
+    Problem::new(err.status_code(), detail).into()
+}
+
+/// Fallback for requests that match no route
+async fn not_found(req: HttpRequest) -> impl Responder {
+    Problem::not_found(format!("No route for {} {}", req.method(), req.path())).error_response()
+}
+
+// OpenAPI 3 specification of the API, generated from the handlers and models
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Rewardo Search API", description = "Search scraped reward flight availability, its history and saved searches"),
+    paths(
+        health_check,
+        latest_reward_flights,
+        cheapest_reward_flights,
+        historic_reward_flights,
+        export_reward_flights,
+        price_history_reward_flights,
+        reward_flight_changes,
+        stream_reward_flight_changes,
+        live_reward_flights,
+        route_statistics,
+        release_pattern,
+        explore_reward_flights,
+        round_trip_reward_flights,
+        calendar_reward_flights,
+        flexible_reward_flights,
+        carrier_summaries,
+        origin_summaries,
+        destination_summaries,
+        list_saved_searches,
+        create_saved_search,
+        get_saved_search,
+        update_saved_search,
+        delete_saved_search,
+        saved_search_matches,
+        notify_saved_search,
+        saved_search_deliveries,
+        webhook_dead_letters,
+        replay_webhook_dead_letter,
+    ),
+    components(schemas(WebhookPayload)),
+    tags(
+        (name = "reward-flights", description = "Searches of the latest scraped reward flights"),
+        (name = "history", description = "Price history, availability changes and route statistics"),
+        (name = "routes", description = "Carriers, origins and destinations with scraped reward flights"),
+        (name = "saved-searches", description = "Saved searches evaluated against the latest reward flights"),
+        (name = "webhooks", description = "Webhook deliveries of saved search matches"),
+        (name = "health", description = "Service health"),
+    )
+)]
+struct ApiDoc;
+
+/// Registers the API's services and the problem responses of its extractors
+fn configure_api(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::QueryConfig::default().error_handler(query_error_handler))
+        .app_data(web::JsonConfig::default().error_handler(json_error_handler))
+        .app_data(web::PathConfig::default().error_handler(path_error_handler))
+        .service(health_check)
+        .service(latest_reward_flights)
+        .service(cheapest_reward_flights)
+        .service(historic_reward_flights)
+        .service(export_reward_flights)
+        .service(price_history_reward_flights)
+        .service(reward_flight_changes)
+        .service(stream_reward_flight_changes)
+        .service(live_reward_flights)
+        .service(route_statistics)
+        .service(release_pattern)
+        .service(explore_reward_flights)
+        .service(round_trip_reward_flights)
+        .service(calendar_reward_flights)
+        .service(flexible_reward_flights)
+        .service(carrier_summaries)
+        .service(origin_summaries)
+        .service(destination_summaries)
+        .service(list_saved_searches)
+        .service(create_saved_search)
+        .service(get_saved_search)
+        .service(update_saved_search)
+        .service(delete_saved_search)
+        .service(saved_search_matches)
+        .service(notify_saved_search)
+        .service(saved_search_deliveries)
+        .service(webhook_dead_letters)
+        .service(replay_webhook_dead_letter);
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // Initialize environment
+    dotenv().ok();
+    env_logger::init();
+
+    info!("Starting server at http://127.0.0.1:8086");
+
+    // Get database URL from environment
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set in .env file");
+    
+    // Create database connection pool and test connection
+    info!("Testing database connection...");
+    let pool = match sqlx::postgres::PgPool::connect(&database_url).await {
+        Ok(pool) => {
+            // Test the connection by executing a simple query
+            match sqlx::query("SELECT 1").execute(&pool).await {
+                Ok(_) => {
+                    info!("Database connection successful");
+                    pool
+                },
+                Err(e) => {
+                    log::error!("Database connection test failed: {}", e);
+                    panic!("Failed to connect to database: {}", e);
+                }
+            }
+        },
+        Err(e) => {
+            log::error!("Failed to create database connection pool: {}", e);
+            panic!("Failed to create database connection pool: {}", e);
+        }
+    };
+
+    // Create or upgrade the tables managed by this service
+    if let Err(e) = sqlx::migrate!().run(&pool).await {
+        log::error!("Database migration failed: {}", e);
+        panic!("Failed to migrate database: {}", e);
+    }
+
+    // Create repositories with database connection
+    let watchlist: web::Data<dyn WatchlistRepository> = web::Data::from(
+        std::sync::Arc::new(SavedSearchRepository::new(pool.clone())) as std::sync::Arc<dyn WatchlistRepository>
+    );
+    let repository: web::Data<dyn RewardFlightRepository> = web::Data::from(
+        std::sync::Arc::new(RewardFlightLatestRepository::new(pool)) as std::sync::Arc<dyn RewardFlightRepository>
+    );
+
+    // Load the carrier registry
+    let carriers = web::Data::new(CarrierRegistry::from_env()?);
+    info!("Serving {} carrier(s)", carriers.carriers().len());
+
+    // Load the airport reference data
+    let airports = web::Data::new(AirportRegistry::from_env()?);
+    info!("Loaded {} airport(s)", airports.airports().len());
+
+    // Send new saved search matches to their webhooks in the background
+    let notifier = web::Data::new(WebhookNotifier::new(
+        repository.clone(),
+        watchlist.clone(),
+        airports.clone(),
+        WebhookConfig::from_env(),
+    ));
+    actix_web::rt::spawn(notifier.get_ref().clone().run());
+
+    // Poll for newly landed scrapes and stream their changes to subscribers
+    let change_poll_seconds = std::env::var("CHANGE_STREAM_POLL_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or(30);
+    let changes = web::Data::new(ChangeBroadcaster::new(1024));
+    actix_web::rt::spawn(changes.get_ref().clone().run(repository.clone(), std::time::Duration::from_secs(change_poll_seconds)));
+
+    // Generate the OpenAPI specification once, served at /openapi.json with Swagger UI
+    let openapi = ApiDoc::openapi();
+
+    // Start HTTP server
+    HttpServer::new(move || {
+        App::new()
+            .app_data(repository.clone())
+            .app_data(watchlist.clone())
+            .app_data(carriers.clone())
+            .app_data(airports.clone())
+            .app_data(notifier.clone())
+            .app_data(changes.clone())
+            .configure(configure_api)
+            .service(SwaggerUi::new("/swagger-ui/{_:.*}").url("/openapi.json", openapi.clone()))
+            .default_service(web::to(not_found))
+    })
+    .bind("0.0.0.0:8086")?
+    .run()
+    .await
+}
+
+#[cfg(test)]
+mod test_fixtures {
+    use super::*;
+
+    /// A LHR to JFK flight of VS with only an economy award of the given points and seats
+    pub fn economy_flight(id: &str, departure: &str, points: Option<i32>, seats: i32) -> RewardFlightLatest {
+        RewardFlightLatest {
+            id: Some(id.to_string()),
+            origin: "LHR".to_string(),
+            destination: "JFK".to_string(),
+            departure: departure.to_string(),
+            carrier_code: "VS".to_string(),
+            scraped_at: Utc::now(),
+            award_economy: Some(AwardEconomy {
+                id: None,
+                cabin_points_value: points,
+                is_saver_award: Some(true),
+                cabin_class_seat_count: Some(seats),
+                cabin_class_seat_count_string: Some(seats.to_string()),
+            }),
+            award_business: None,
+            award_premium_economy: None,
+            award_first: None,
+            deal_scores: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod flexible_date_search_tests {
+    use super::*;
+    use crate::test_fixtures::economy_flight;
+
+    fn date(date: &str) -> NaiveDate {
+        date.parse().unwrap()
+    }
+
+    fn search(flights: Vec<RewardFlightLatest>) -> FlexibleDateSearch {
+        FlexibleDateSearch::from_flights(date("2026-11-05"), 3, CabinType::Economy, flights)
+    }
+
+    #[test]
+    fn keeps_the_cheapest_flight_of_each_day() {
+        let search = search(vec![
+            economy_flight("dear", "2026-11-05", Some(20000), 2),
+            economy_flight("cheap", "2026-11-05", Some(15000), 1),
+            economy_flight("dearer", "2026-11-05", Some(25000), 4),
+        ]);
+
+        assert_eq!(search.options.len(), 1);
+        let option = &search.options[0];
+        assert_eq!(option.cabin_points_value, 15000);
+        assert_eq!(option.flight.id.as_deref(), Some("cheap"));
+        assert_eq!(option.flights_available, 3);
+        assert_eq!(option.days_from_target, 0);
+    }
+
+    #[test]
+    fn keeps_the_first_flight_of_a_day_on_equal_points() {
+        let search = search(vec![
+            economy_flight("first", "2026-11-05", Some(15000), 1),
+            economy_flight("second", "2026-11-05", Some(15000), 1),
+        ]);
+
+        assert_eq!(search.options[0].flight.id.as_deref(), Some("first"));
+        assert_eq!(search.options[0].flights_available, 2);
+    }
+
+    #[test]
+    fn orders_by_points_then_proximity_then_date() {
+        let search = search(vec![
+            economy_flight("later", "2026-11-06", Some(15000), 1),
+            economy_flight("far", "2026-11-08", Some(15000), 1),
+            economy_flight("earlier", "2026-11-04", Some(15000), 1),
+            economy_flight("cheapest", "2026-11-02", Some(10000), 1),
+            economy_flight("target", "2026-11-05", Some(20000), 1),
+        ]);
+
+        let dates: Vec<NaiveDate> = search.options.iter().map(|option| option.date).collect();
+        assert_eq!(dates, vec![date("2026-11-02"), date("2026-11-04"), date("2026-11-06"), date("2026-11-08"), date("2026-11-05")]);
+        let days_from_target: Vec<i64> = search.options.iter().map(|option| option.days_from_target).collect();
+        assert_eq!(days_from_target, vec![-3, -1, 1, 3, 0]);
+    }
+
+    #[test]
+    fn skips_flights_that_cannot_be_booked() {
+        let search = search(vec![
+            economy_flight("sold-out", "2026-11-05", Some(10000), 0),
+            economy_flight("unpriced", "2026-11-06", None, 2),
+            economy_flight("undated", "soon", Some(10000), 2),
+            economy_flight("bookable", "2026-11-07", Some(30000), 1),
+        ]);
+
+        assert_eq!(search.options.len(), 1);
+        assert_eq!(search.options[0].flight.id.as_deref(), Some("bookable"));
+        assert_eq!(search.options[0].flights_available, 1);
+    }
+
+    #[test]
+    fn has_no_options_without_flights() {
+        let search = search(Vec::new());
+
+        assert!(search.options.is_empty());
+        assert_eq!(search.target_date, date("2026-11-05"));
+        assert_eq!(search.flex_days, 3);
+    }
+}
+
+#[cfg(test)]
+mod deal_score_tests {
+    use super::*;
+
+    #[test]
+    fn has_no_rank_without_history() {
+        let score = DealScore::new(10000, 0, 0, None, 90);
+
+        assert_eq!(score.percentile_rank, None);
+        assert!(score.lowest_seen);
+        assert_eq!(score.lowest_points, None);
+        assert_eq!((score.sample_size, score.lookback_days), (0, 90));
+    }
+
+    #[test]
+    fn ranks_against_a_single_sample() {
+        let same = DealScore::new(10000, 1, 0, Some(10000), 90);
+        assert_eq!(same.percentile_rank, Some(0.0));
+        assert!(same.lowest_seen);
+
+        let dearer = DealScore::new(12000, 1, 1, Some(10000), 90);
+        assert_eq!(dearer.percentile_rank, Some(100.0));
+        assert!(!dearer.lowest_seen);
+
+        let cheaper = DealScore::new(8000, 1, 0, Some(10000), 90);
+        assert_eq!(cheaper.percentile_rank, Some(0.0));
+        assert!(cheaper.lowest_seen);
+    }
+
+    #[test]
+    fn ranks_by_the_share_of_strictly_lower_prices() {
+        let score = DealScore::new(15000, 8, 2, Some(10000), 30);
+
+        assert_eq!(score.percentile_rank, Some(25.0));
+        assert!(!score.lowest_seen);
+        assert_eq!(score.lowest_points, Some(10000));
+    }
+
+    #[test]
+    fn counts_a_tie_with_the_lowest_price_as_lowest_seen() {
+        // Equal historic prices are not lower, so a tie still ranks first
+        let score = DealScore::new(10000, 5, 0, Some(10000), 90);
+
+        assert_eq!(score.percentile_rank, Some(0.0));
+        assert!(score.lowest_seen);
+    }
+}
+
+#[cfg(test)]
+mod release_timing_tests {
+    use super::*;
+
+    fn histogram(counts: &[(i32, i64)]) -> Vec<ReleaseDayCount> {
+        counts
+            .iter()
+            .map(|&(days_before, event_count)| ReleaseDayCount { days_before, event_count })
+            .collect()
+    }
+
+    fn summary(timing: &ReleaseTiming) -> (i64, Option<i32>, Option<i32>, Option<i32>) {
+        (timing.event_count, timing.min_days_before, timing.median_days_before, timing.max_days_before)
+    }
+
+    #[test]
+    fn has_no_timing_without_observations() {
+        let timing = ReleaseTiming::from_histogram(Vec::new());
+
+        assert_eq!(summary(&timing), (0, None, None, None));
+        assert!(timing.days_before.is_empty());
+    }
+
+    #[test]
+    fn summarises_a_single_observation() {
+        let timing = ReleaseTiming::from_histogram(histogram(&[(14, 1)]));
+
+        assert_eq!(summary(&timing), (1, Some(14), Some(14), Some(14)));
+    }
+
+    #[test]
+    fn weights_the_median_by_event_count() {
+        let timing = ReleaseTiming::from_histogram(histogram(&[(0, 1), (3, 5), (330, 1)]));
+
+        assert_eq!(summary(&timing), (7, Some(0), Some(3), Some(330)));
+        assert_eq!(timing.days_before.len(), 3);
+    }
+
+    #[test]
+    fn takes_the_lower_median_of_an_even_count() {
+        let split = ReleaseTiming::from_histogram(histogram(&[(1, 1), (5, 1)]));
+        assert_eq!(split.median_days_before, Some(1));
+
+        // The median rank falls on the last observation of the first day
+        let tied = ReleaseTiming::from_histogram(histogram(&[(2, 2), (7, 2)]));
+        assert_eq!(summary(&tied), (4, Some(2), Some(2), Some(7)));
+    }
+}
+
+#[cfg(test)]
+mod change_filter_tests {
+    use super::*;
+
+    // Points, seats and saver flag of an award
+    type Award = (i32, i32, bool);
+
+    /// A snapshot scraped on the given hour of 1 October 2026, with economy and business awards
+    fn snapshot(hour: u32, economy: Option<Award>, business: Option<Award>) -> RewardFlightLatestHistoric {
+        RewardFlightLatestHistoric {
+            id: None,
+            origin: "LHR".to_string(),
+            destination: "JFK".to_string(),
+            departure: "2026-11-02".to_string(),
+            carrier_code: "VS".to_string(),
+            scraped_at: scraped_at(hour),
+            award_economy: economy.map(|(points, seats, saver)| AwardEconomy {
+                id: None,
+                cabin_points_value: Some(points),
+                is_saver_award: Some(saver),
+                cabin_class_seat_count: Some(seats),
+                cabin_class_seat_count_string: Some(seats.to_string()),
+            }),
+            award_business: business.map(|(points, seats, saver)| AwardBusiness {
+                id: None,
+                cabin_points_value: Some(points),
+                is_saver_award: Some(saver),
+                cabin_class_seat_count: Some(seats),
+                cabin_class_seat_count_string: Some(seats.to_string()),
+            }),
+            award_premium_economy: None,
+            award_first: None,
+        }
+    }
+
+    fn scraped_at(hour: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(2026, 10, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap().and_utc()
+    }
+
+    fn events(changes: &[AvailabilityChange]) -> Vec<(CabinType, ChangeEventType)> {
+        changes.iter().map(|change| (change.cabin_type, change.event_type)).collect()
+    }
+
+    #[test]
+    fn detects_each_event_type() {
+        let previous = snapshot(1, Some((10000, 0, true)), Some((50000, 2, true)));
+        let current = snapshot(2, Some((10000, 3, true)), Some((50000, 0, false)));
+        let changes = ChangeFilter::default().changes_between(&previous, &current);
+
+        assert_eq!(events(&changes), vec![
+            (CabinType::Economy, ChangeEventType::SeatsOpened),
+            (CabinType::Business, ChangeEventType::SeatsSoldOut),
+            (CabinType::Business, ChangeEventType::SaverChanged),
+        ]);
+        let opened = &changes[0];
+        assert_eq!((opened.previous_seats, opened.seats), (Some(0), Some(3)));
+        assert_eq!((opened.previous_scraped_at, opened.scraped_at), (scraped_at(1), scraped_at(2)));
+        assert_eq!(opened.departure, NaiveDate::from_ymd_opt(2026, 11, 2).unwrap());
+    }
+
+    #[test]
+    fn reports_every_change_of_a_cabin_that_opens_and_moves() {
+        let previous = snapshot(1, Some((10000, 0, true)), None);
+        let current = snapshot(2, Some((12000, 2, false)), None);
+        let changes = ChangeFilter::default().changes_between(&previous, &current);
+
+        assert_eq!(events(&changes), vec![
+            (CabinType::Economy, ChangeEventType::SeatsOpened),
+            (CabinType::Economy, ChangeEventType::PointsChanged),
+            (CabinType::Economy, ChangeEventType::SaverChanged),
+        ]);
+        assert!(changes.iter().all(|change| change.previous_points == Some(10000) && change.points == Some(12000)));
+    }
+
+    #[test]
+    fn counts_a_missing_award_as_no_seats() {
+        let previous = snapshot(1, None, Some((50000, 1, true)));
+        let current = snapshot(2, Some((10000, 4, true)), None);
+        let changes = ChangeFilter::default().changes_between(&previous, &current);
+
+        // Prices and saver flags only change between two awards
+        assert_eq!(events(&changes), vec![
+            (CabinType::Economy, ChangeEventType::SeatsOpened),
+            (CabinType::Business, ChangeEventType::SeatsSoldOut),
+        ]);
+    }
+
+    #[test]
+    fn reports_nothing_between_equal_snapshots() {
+        let previous = snapshot(1, Some((10000, 3, true)), Some((50000, 2, false)));
+        let current = snapshot(2, Some((10000, 3, true)), Some((50000, 2, false)));
+
+        assert!(ChangeFilter::default().changes_between(&previous, &current).is_empty());
+    }
+
+    #[test]
+    fn keeps_only_the_filtered_cabin_and_event_types() {
+        let previous = snapshot(1, Some((10000, 0, true)), Some((50000, 2, true)));
+        let current = snapshot(2, Some((12000, 3, true)), Some((55000, 2, true)));
+        let filter = ChangeFilter {
+            cabin_type: Some(CabinType::Business),
+            event_types: Some(vec![ChangeEventType::PointsChanged]),
+            since: None,
         };
-        
-        // Calculate total pages
-        let total_pages = (total_elements as f64 / page_size as f64).ceil() as usize;
-        
-        Ok(Page {
-            content: paginated_flights,
-            page_number,
-            page_size,
-            total_elements,
-            total_pages,
-        })
+        let changes = filter.changes_between(&previous, &current);
+
+        assert_eq!(events(&changes), vec![(CabinType::Business, ChangeEventType::PointsChanged)]);
+        assert!(changes.iter().all(|change| filter.matches(change)));
+        let all_changes = ChangeFilter::default().changes_between(&previous, &current);
+        assert_eq!(all_changes.iter().filter(|change| filter.matches(change)).count(), 1);
+    }
+
+    #[test]
+    fn skips_changes_scraped_before_since() {
+        let previous = snapshot(1, Some((10000, 0, true)), None);
+        let current = snapshot(2, Some((10000, 3, true)), None);
+        let at = |hour| ChangeFilter { since: Some(scraped_at(hour)), ..ChangeFilter::default() };
+
+        assert!(at(3).changes_between(&previous, &current).is_empty());
+        let changes = at(2).changes_between(&previous, &current);
+        assert_eq!(events(&changes), vec![(CabinType::Economy, ChangeEventType::SeatsOpened)]);
+        assert!(at(2).matches(&changes[0]));
+        assert!(!at(3).matches(&changes[0]));
+    }
+
+    #[test]
+    fn skips_snapshots_without_a_departure_date() {
+        let previous = snapshot(1, Some((10000, 0, true)), None);
+        let current = RewardFlightLatestHistoric { departure: "unknown".to_string(), ..snapshot(2, Some((10000, 3, true)), None) };
+
+        assert!(ChangeFilter::default().changes_between(&previous, &current).is_empty());
+    }
+}
+
+#[cfg(test)]
+mod webhook_tests {
+    use super::*;
+
+    const SECRET: &str = "s3cret";
+
+    // A request received by the webhook stub
+    struct StubRequest {
+        uri: String,
+        received_at: std::time::Instant,
+        signature: Option<String>,
+        body: web::Bytes,
+    }
+
+    // A local webhook endpoint failing its first `failures` requests with a 500
+    #[derive(Clone)]
+    struct WebhookStub {
+        failures: usize,
+        requests: std::sync::Arc<std::sync::Mutex<Vec<StubRequest>>>,
+    }
+
+    impl WebhookStub {
+        /// Starts the stub on an ephemeral local port, returning its URL
+        fn start(failures: usize) -> (Self, String) {
+            let stub = Self { failures, requests: Default::default() };
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("stub binds a local port");
+            let url = format!("http://{}/hook", listener.local_addr().expect("stub has an address"));
+            let state = stub.clone();
+            let server = HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(state.clone()))
+                    .route("/hook", web::post().to(Self::receive))
+            })
+            .workers(1)
+            .listen(listener)
+            .expect("stub listens")
+            .run();
+            actix_web::rt::spawn(server);
+            (stub, url)
+        }
+
+        async fn receive(req: HttpRequest, body: web::Bytes, stub: web::Data<WebhookStub>) -> HttpResponse {
+            let mut requests = stub.requests.lock().expect("stub requests lock");
+            requests.push(StubRequest {
+                uri: req.uri().to_string(),
+                received_at: std::time::Instant::now(),
+                signature: req.headers().get("X-Rewardo-Signature").and_then(|value| value.to_str().ok()).map(str::to_string),
+                body,
+            });
+            if requests.len() <= stub.failures {
+                HttpResponse::InternalServerError().finish()
+            } else {
+                HttpResponse::Ok().finish()
+            }
+        }
+
+        fn request_count(&self) -> usize {
+            self.requests.lock().expect("stub requests lock").len()
+        }
+    }
+
+    /// Settings allowing delivery to the local stub, with short backoffs
+    fn config(max_attempts: u32) -> WebhookConfig {
+        WebhookConfig {
+            poll_interval: std::time::Duration::from_secs(60),
+            max_attempts,
+            initial_backoff: std::time::Duration::from_millis(50),
+            request_timeout: std::time::Duration::from_secs(5),
+            concurrency: 4,
+            allow_private_networks: true,
+        }
+    }
+
+    /// A notifier over the mock repositories, with one saved search posting to `url`
+    async fn notifier(url: &str, config: WebhookConfig) -> (WebhookNotifier, web::Data<dyn WatchlistRepository>, SavedSearch) {
+        let repo: web::Data<dyn RewardFlightRepository> = web::Data::from(
+            std::sync::Arc::new(MockRewardFlightRepository) as std::sync::Arc<dyn RewardFlightRepository>
+        );
+        let watchlist: web::Data<dyn WatchlistRepository> = web::Data::from(
+            std::sync::Arc::new(MockWatchlistRepository::default()) as std::sync::Arc<dyn WatchlistRepository>
+        );
+        let saved_search = watchlist.create_saved_search(&saved_search_request(url)).await.unwrap();
+        let notifier = WebhookNotifier::new(repo, watchlist.clone(), web::Data::new(AirportRegistry::default()), config);
+        (notifier, watchlist, saved_search)
+    }
+
+    /// A saved search of LHR to JFK business class posting to `url`
+    fn saved_search_request(url: &str) -> SavedSearchRequest {
+        SavedSearchRequest {
+            name: "New York".to_string(),
+            carrier_code: "VS".to_string(),
+            origin: "LHR".to_string(),
+            destination: "JFK".to_string(),
+            departure_from: NaiveDate::from_ymd_opt(2026, 11, 2).unwrap(),
+            departure_to: NaiveDate::from_ymd_opt(2026, 11, 4).unwrap(),
+            cabin_type: CabinType::Business,
+            min_seats: Some(1),
+            max_points: None,
+            webhook_url: Some(url.to_string()),
+            webhook_secret: Some(SECRET.to_string()),
+        }
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        assert_eq!(
+            webhook_signature("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[actix_web::test]
+    async fn delivers_signed_payloads() {
+        let (stub, url) = WebhookStub::start(0);
+        let (notifier, _, saved_search) = notifier(&url, config(3)).await;
+
+        let delivery = notifier.notify(&saved_search).await.unwrap().expect("matches are delivered");
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.response_status, Some(200));
+
+        let requests = stub.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].signature.as_deref(), Some(webhook_signature(SECRET, &requests[0].body).as_str()));
+        assert_ne!(requests[0].signature.as_deref(), Some(webhook_signature("wrong", &requests[0].body).as_str()));
+        let payload: WebhookPayload = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(payload.saved_search_id, saved_search.id);
+        assert_eq!(payload.matches.len(), 3);
+        assert!(payload.matches.iter().all(|webhook_match| webhook_match.cabin_type == CabinType::Business));
+    }
+
+    #[actix_web::test]
+    async fn retries_with_exponential_backoff() {
+        let (stub, url) = WebhookStub::start(2);
+        let (notifier, watchlist, saved_search) = notifier(&url, config(5)).await;
+
+        let delivery = notifier.notify(&saved_search).await.unwrap().expect("matches are delivered");
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.attempts, 3);
+        assert!(watchlist.find_webhook_dead_letters(0, 10).await.unwrap().content.is_empty());
+
+        // Waits of 50ms then 100ms between the attempts
+        let requests = stub.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].received_at - requests[0].received_at >= std::time::Duration::from_millis(50));
+        assert!(requests[2].received_at - requests[1].received_at >= std::time::Duration::from_millis(100));
+    }
+
+    #[actix_web::test]
+    async fn dead_letters_after_max_attempts() {
+        let (stub, url) = WebhookStub::start(usize::MAX);
+        let (notifier, watchlist, saved_search) = notifier(&url, config(3)).await;
+
+        let delivery = notifier.notify(&saved_search).await.unwrap().expect("a failed delivery is logged");
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.response_status, Some(500));
+        assert_eq!(stub.request_count(), 3);
+
+        let dead_letters = watchlist.find_webhook_dead_letters(0, 10).await.unwrap().content;
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].delivery_id, delivery.id);
+        assert_eq!(dead_letters[0].saved_search_id, saved_search.id);
+
+        // The prices of a dead letter are not sent again by later polls
+        assert!(notifier.notify(&saved_search).await.unwrap().is_none());
+        assert_eq!(stub.request_count(), 3);
+        assert_eq!(watchlist.find_webhook_dead_letters(0, 10).await.unwrap().total_elements, 1);
+    }
+
+    #[actix_web::test]
+    async fn replays_dead_letters() {
+        let (stub, url) = WebhookStub::start(3);
+        let (notifier, watchlist, saved_search) = notifier(&url, config(3)).await;
+
+        let delivery = notifier.notify(&saved_search).await.unwrap().expect("a failed delivery is logged");
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        let dead_letter = watchlist.find_webhook_dead_letters(0, 10).await.unwrap().content.remove(0);
+
+        let replayed = notifier.replay(&dead_letter, &saved_search).await.unwrap();
+        assert_eq!(replayed.status, DeliveryStatus::Delivered);
+        assert_eq!(replayed.payload, dead_letter.payload);
+        assert_eq!(stub.request_count(), 4);
+        assert!(watchlist.find_webhook_dead_letters(0, 10).await.unwrap().content.is_empty());
+    }
+
+    #[test]
+    fn rejects_webhook_urls_of_non_public_hosts() {
+        let rejected = [
+            "ftp://example.com/hook",
+            "http://localhost:8080/hook",
+            "http://hooks.localhost/hook",
+            "http://metadata.google.internal/computeMetadata/v1/",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3/hook",
+            "http://172.16.0.1/hook",
+            "http://192.168.1.1/hook",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fd00:ec2::254]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://[::127.0.0.1]/hook",
+            "http://[::a9fe:a9fe]/hook",
+            "http://[64:ff9b::a9fe:a9fe]/hook",
+            "http://[64:ff9b::10.0.0.1]/hook",
+            "http://[64:ff9b:1::a9fe:a9fe]/hook",
+            "http://[2002:a9fe:a9fe::1]/hook",
+            "http://[2002:c0a8:0101::]/hook",
+        ];
+        for url in rejected {
+            assert!(check_webhook_url(url, false).is_err(), "{}", url);
+        }
+
+        assert!(check_webhook_url("https://example.com/hook", false).is_ok());
+        assert!(check_webhook_url("http://93.184.216.34/hook", false).is_ok());
+        assert!(check_webhook_url("http://[64:ff9b::5db8:d822]/hook", false).is_ok());
+        assert!(check_webhook_url("http://[2002:5db8:d822::1]/hook", false).is_ok());
+        assert!(check_webhook_url("http://[2606:2800:220:1::]/hook", false).is_ok());
+        assert!(check_webhook_url("http://127.0.0.1/hook", true).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn resolves_host_names_to_public_addresses_only() {
+        use reqwest::dns::Resolve;
+
+        let name: reqwest::dns::Name = "localhost".parse().unwrap();
+        assert!(PublicAddressResolver.resolve(name).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn does_not_deliver_to_private_addresses() {
+        let (stub, url) = WebhookStub::start(0);
+        let config = WebhookConfig { allow_private_networks: false, ..config(3) };
+        let (notifier, watchlist, saved_search) = notifier(&url, config).await;
+
+        let delivery = notifier.notify(&saved_search).await.unwrap().expect("a failed delivery is logged");
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.attempts, 0);
+        assert_eq!(stub.request_count(), 0);
+        assert_eq!(watchlist.find_webhook_dead_letters(0, 10).await.unwrap().total_elements, 1);
+    }
+
+    #[actix_web::test]
+    async fn notifies_saved_searches_concurrently() {
+        let (stub, url) = WebhookStub::start(usize::MAX);
+        let (notifier, watchlist, _) = notifier(&format!("{}?search=1", url), config(3)).await;
+        watchlist.create_saved_search(&saved_search_request(&format!("{}?search=2", url))).await.unwrap();
+
+        notifier.notify_all().await.unwrap();
+
+        // Both retry at once, rather than the second waiting on every attempt of the first
+        let requests = stub.requests.lock().unwrap();
+        assert_eq!(requests.len(), 6);
+        let received = |search: &str| -> Vec<std::time::Instant> {
+            requests
+                .iter()
+                .filter(|request| request.uri.ends_with(search))
+                .map(|request| request.received_at)
+                .collect()
+        };
+        let (first, second) = (received("search=1"), received("search=2"));
+        assert_eq!(first.len(), 3);
+        assert_eq!(second.len(), 3);
+        assert!(second[0] < first[2]);
+        assert!(first[0] < second[2]);
+    }
+
+    #[actix_web::test]
+    async fn does_not_notify_the_same_prices_twice() {
+        let (stub, url) = WebhookStub::start(0);
+        let (notifier, watchlist, saved_search) = notifier(&url, config(3)).await;
+
+        assert!(notifier.notify(&saved_search).await.unwrap().is_some());
+        assert!(notifier.notify(&saved_search).await.unwrap().is_none());
+        assert_eq!(stub.request_count(), 1);
+
+        let deliveries = watchlist.find_webhook_deliveries_by_saved_search_id(saved_search.id, 0, 10).await.unwrap();
+        assert_eq!(deliveries.total_elements, 1);
     }
 }
 
-/// Handler for retrieving the latest reward flights based on search criteria
-///
-/// # Parameters
-/// * `origin` - The origin airport code (e.g., "LHR")
-/// * `destination` - The destination airport code (e.g., "JFK")
-/// * `from` - The start date for the search in YYYY-MM-DD format
-/// * `to` - The end date for the search in YYYY-MM-DD format
-/// * `page-number` - The page number for pagination (default: 0)
-/// * `page-size` - The number of items per page (default: 10)
-///
-/// # Returns
-/// A paginated list of reward flights matching the criteria
-#[get("/api/v1/airline/vs/reward-flights/origin/{origin}/destination/{destination}/from/{from}/to/{to}")]
-async fn latest_reward_flights(
-    path: web::Path<(String, String, String, String)>,
-    query: web::Query<PageParams>,
-    repo: web::Data<RewardFlightLatestRepository>,
-) -> impl Responder {
-    let (origin, destination, from, to) = path.into_inner();
-    let page_number = query.page_number.unwrap_or(0);
-    let page_size = query.page_size.unwrap_or(10);
+#[cfg(test)]
+mod live_search_message_tests {
+    use super::*;
+    use crate::test_fixtures::economy_flight;
 
-    // Parse dates
-    let from_date = match NaiveDate::parse_from_str(&from, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid 'from' date format. Expected YYYY-MM-DD"),
-    };
+    /// A flight departing on the given November 2026 day, with an economy award of the given points
+    fn flight(day: u32, points: i32) -> RewardFlightLatest {
+        let departure = format!("2026-11-{:02}", day);
+        economy_flight(&departure, &departure, Some(points), 2)
+    }
 
-    let to_date = match NaiveDate::parse_from_str(&to, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid 'to' date format. Expected YYYY-MM-DD"),
-    };
+    /// Each message's type and the departure and economy points of its flight
+    fn summary(messages: &[LiveSearchMessage]) -> Vec<(&'static str, String, Option<i32>)> {
+        messages
+            .iter()
+            .map(|message| {
+                let (kind, flight) = match message {
+                    LiveSearchMessage::Added { flight } => ("ADDED", flight),
+                    LiveSearchMessage::Updated { flight } => ("UPDATED", flight),
+                    LiveSearchMessage::Removed { flight } => ("REMOVED", flight),
+                    other => panic!("Unexpected message {:?}", other),
+                };
+                (kind, flight.departure.clone(), flight.cabin_points_value(CabinType::Economy))
+            })
+            .collect()
+    }
 
-    // Query the repository
-    match repo.find_by_origin_and_destination_and_carrier_code_and_departure_between(
-        &origin,
-        &destination,
-        "VS",
-        from_date,
-        to_date,
-        page_number as usize,
-        page_size as usize,
-    ).await {
-        Ok(page) => HttpResponse::Ok().json(page),
-        Err(e) => {
-            log::error!("Database error: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch reward flights")
-        }
+    #[test]
+    fn adds_every_flight_of_a_first_result() {
+        let messages = LiveSearchMessage::updates(&[], &[flight(1, 10000), flight(2, 12000)]);
+
+        assert_eq!(summary(&messages), vec![
+            ("ADDED", "2026-11-01".to_string(), Some(10000)),
+            ("ADDED", "2026-11-02".to_string(), Some(12000)),
+        ]);
+    }
+
+    #[test]
+    fn removes_every_flight_of_an_empty_result() {
+        let messages = LiveSearchMessage::updates(&[flight(1, 10000), flight(2, 12000)], &[]);
+
+        assert_eq!(summary(&messages), vec![
+            ("REMOVED", "2026-11-01".to_string(), Some(10000)),
+            ("REMOVED", "2026-11-02".to_string(), Some(12000)),
+        ]);
+    }
+
+    #[test]
+    fn sends_nothing_for_a_rescrape_without_changes() {
+        let previous = vec![flight(1, 10000), flight(2, 12000)];
+        let current = vec![RewardFlightLatest { scraped_at: Utc::now() + chrono::Duration::hours(1), ..flight(1, 10000) }, flight(2, 12000)];
+
+        assert!(LiveSearchMessage::updates(&previous, &current).is_empty());
+    }
+
+    #[test]
+    fn sends_nothing_for_a_flight_that_only_moves() {
+        let previous = vec![flight(1, 10000), flight(2, 12000)];
+        let current = vec![flight(2, 12000), flight(1, 10000)];
+
+        assert!(LiveSearchMessage::updates(&previous, &current).is_empty());
+    }
+
+    #[test]
+    fn updates_a_flight_that_both_moves_and_changes() {
+        let previous = vec![flight(1, 10000), flight(2, 12000)];
+        let current = vec![flight(2, 12000), flight(1, 9000)];
+
+        assert_eq!(summary(&LiveSearchMessage::updates(&previous, &current)), vec![
+            ("UPDATED", "2026-11-01".to_string(), Some(9000)),
+        ]);
+    }
+
+    #[actix_web::test]
+    async fn diffs_every_page_of_the_search() {
+        let request: LiveSearchRequest = serde_json::from_str(
+            r#"{"origin": "LHR", "destination": "JFK", "from": "2026-11-02", "to": "2026-11-08", "page-size": 2}"#,
+        ).unwrap();
+        let carriers = CarrierRegistry::default();
+        let search = request.search(carriers.find("vs").unwrap(), &AirportRegistry::default()).unwrap();
+
+        let page = search.find(&MockRewardFlightRepository).await.unwrap();
+        let flights = search.find_all(&MockRewardFlightRepository).await.unwrap();
+        assert_eq!(page.content.len(), 2);
+        assert_eq!(flights.len(), 7);
+
+        // A flight pushed off the snapshot's page by an earlier departure is still the same result
+        let mut moved = vec![flight(1, 10000)];
+        moved.extend(flights.iter().cloned());
+        assert_eq!(summary(&LiveSearchMessage::updates(&flights, &moved)), vec![
+            ("ADDED", "2026-11-01".to_string(), Some(10000)),
+        ]);
+    }
+
+    #[test]
+    fn covers_scraped_departures_of_its_routes_and_dates() {
+        let request: LiveSearchRequest = serde_json::from_str(
+            r#"{"origin": "LON", "destination": "JFK", "from": "2026-11-02", "to": "2026-11-08"}"#,
+        ).unwrap();
+        let carriers = CarrierRegistry::default();
+        let search = request.search(carriers.find("vs").unwrap(), &AirportRegistry::default()).unwrap();
+        let departure = |origin: &str, day: u32| ScrapedDeparture {
+            origin: origin.to_string(),
+            destination: "JFK".to_string(),
+            carrier_code: "VS".to_string(),
+            departure: NaiveDate::from_ymd_opt(2026, 11, day).unwrap(),
+        };
+
+        assert!(search.covers(&departure("LHR", 2)));
+        assert!(search.covers(&departure("LGW", 8)));
+        assert!(!search.covers(&departure("LHR", 9)));
+        assert!(!search.covers(&departure("MAN", 4)));
+
+        let window = search.departing_between(NaiveDate::from_ymd_opt(2026, 11, 4).unwrap(), NaiveDate::from_ymd_opt(2026, 11, 5).unwrap());
+        assert!(window.departs_within(&flight(4, 10000)));
+        assert!(!window.departs_within(&flight(6, 10000)));
+    }
+
+    #[actix_web::test]
+    async fn rejects_searches_over_the_live_result_cap() {
+        let request: LiveSearchRequest = serde_json::from_str(
+            r#"{"origin": "LON", "destination": "NYC", "from": "2026-11-01", "to": "2027-10-31"}"#,
+        ).unwrap();
+        let carriers = CarrierRegistry::default();
+        let search = request.search(carriers.find("vs").unwrap(), &AirportRegistry::default()).unwrap();
+
+        let problem = start_live_search(&MockRewardFlightRepository, &search).await.unwrap_err();
+        assert_eq!(problem.status, 400);
+    }
+
+    #[test]
+    fn sends_removals_before_additions_and_updates() {
+        let previous = vec![flight(1, 10000), flight(2, 12000)];
+        let current = vec![flight(2, 11000), flight(3, 10000)];
+
+        assert_eq!(summary(&LiveSearchMessage::updates(&previous, &current)), vec![
+            ("REMOVED", "2026-11-01".to_string(), Some(10000)),
+            ("UPDATED", "2026-11-02".to_string(), Some(11000)),
+            ("ADDED", "2026-11-03".to_string(), Some(10000)),
+        ]);
     }
 }
 
-/// Health check endpoint that returns a 200 OK response
-///
-/// # Returns
-/// A 200 OK response with a simple message
-#[get("/health")]
-async fn health_check() -> impl Responder {
-    HttpResponse::Ok().body("OK")
+#[cfg(test)]
+mod carrier_registry_tests {
+    use super::*;
+
+    #[test]
+    fn default_carrier_supports_first_class() {
+        let registry = CarrierRegistry::default();
+        let carrier = registry.find("vs").expect("VS is a default carrier");
+        let cabin_type = "FIRST".parse::<CabinType>().expect("FIRST is a cabin type");
+
+        assert_eq!(cabin_type, CabinType::First);
+        assert!(carrier.supports_cabin(cabin_type));
+    }
+
+    #[test]
+    fn finds_carriers_case_insensitively() {
+        let registry = CarrierRegistry::default();
+
+        assert!(registry.find("VS").is_some());
+        assert!(registry.find("vs").is_some());
+        assert!(registry.find("BA").is_none());
+    }
 }
 
-/// Handler for retrieving the cheapest reward flights based on origin, destination, and cabin type
-///
-/// # Parameters
-/// * `origin` - The origin airport code (e.g., "LHR")
-/// * `destination` - The destination airport code (e.g., "JFK")
-/// * `cabinType` - The cabin type (ECONOMY, PREMIUM_ECONOMY, BUSINESS)
-/// * `page-number` - The page number for pagination (default: 0)
-/// * `page-size` - The number of items per page (default: 50)
-///
-/// # Returns
-/// A paginated list of reward flights ordered by lowest cabin points
-#[get("/api/v1/airline/vs/reward-flights/origin/{origin}/destination/{destination}/cabin/{cabin_type}/cheapest")]
-async fn cheapest_reward_flights(
-    path: web::Path<(String, String, String)>,
-    query: web::Query<PageParams>,
-    repo: web::Data<RewardFlightLatestRepository>,
-) -> impl Responder {
-    let (origin, destination, cabin_type_str) = path.into_inner();
-    let page_number = query.page_number.unwrap_or(0);
-    let page_size = query.page_size.unwrap_or(50);
-    
-    // Validate cabin type
-    let cabin_type = match cabin_type_str.as_str() {
-        "ECONOMY" | "PREMIUM_ECONOMY" | "BUSINESS" => cabin_type_str,
-        _ => return HttpResponse::BadRequest().body("Invalid cabin type. Expected ECONOMY, PREMIUM_ECONOMY, or BUSINESS"),
-    };
+#[cfg(test)]
+mod handler_tests {
+    use super::*;
+    use actix_web::test;
 
-    // Query the repository
-    match repo.find_all_ordered_by_lowest_cabin_points_and_origin_and_destination(
-        &origin,
-        &destination,
-        &cabin_type,
-        page_number as usize,
-        page_size as usize,
-    ).await {
-        Ok(page) => HttpResponse::Ok().json(page),
-        Err(e) => {
-            log::error!("Database error: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch cheapest reward flights")
+    /// The API backed by the mock repositories, with the default carriers and bundled airports
+    macro_rules! mock_api {
+        () => {
+            mock_api!(web::Data::from(
+                std::sync::Arc::new(MockWatchlistRepository::default()) as std::sync::Arc<dyn WatchlistRepository>
+            ))
+        };
+        ($watchlist:expr) => {{
+            let repository: web::Data<dyn RewardFlightRepository> = web::Data::from(
+                std::sync::Arc::new(MockRewardFlightRepository) as std::sync::Arc<dyn RewardFlightRepository>
+            );
+            let watchlist: web::Data<dyn WatchlistRepository> = $watchlist;
+            let airports = web::Data::new(AirportRegistry::default());
+            let notifier = web::Data::new(WebhookNotifier::new(
+                repository.clone(),
+                watchlist.clone(),
+                airports.clone(),
+                WebhookConfig::from_env(),
+            ));
+            test::init_service(
+                App::new()
+                    .app_data(repository)
+                    .app_data(watchlist)
+                    .app_data(web::Data::new(CarrierRegistry::default()))
+                    .app_data(airports)
+                    .app_data(notifier)
+                    .app_data(web::Data::new(ChangeBroadcaster::new(16)))
+                    .configure(configure_api)
+                    .default_service(web::to(not_found)),
+            ).await
+        }};
+    }
+
+    const LATEST: &str = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-02/to/2026-11-08";
+
+    #[actix_web::test]
+    async fn latest_returns_a_page_of_flights() {
+        let api = mock_api!();
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(LATEST).to_request()).await;
+
+        assert_eq!(page.total_elements, 7);
+        assert_eq!(page.content.len(), 7);
+        assert!(page.content.iter().all(|flight| flight.origin == "LHR" && flight.destination == "JFK" && flight.carrier_code == "VS"));
+    }
+
+    #[actix_web::test]
+    async fn latest_filters_and_paginates() {
+        let api = mock_api!();
+        let uri = format!("{}?weekends-only=true&page-size=1", LATEST);
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+
+        // 2026-11-07 and 2026-11-08 are the only weekend days of the window
+        assert_eq!(page.total_elements, 2);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.content[0].departure, "2026-11-07");
+
+        let uri = format!("{}?min-seats=6", LATEST);
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(page.total_elements, 0);
+    }
+
+    #[actix_web::test]
+    async fn latest_expands_metro_areas() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LON/destination/JFK/from/2026-11-02/to/2026-11-02";
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        let origins: Vec<&str> = page.content.iter().map(|flight| flight.origin.as_str()).collect();
+        assert!(origins.len() > 1);
+        assert!(origins.contains(&"LHR"));
+        assert!(origins.contains(&"LGW"));
+    }
+
+    #[actix_web::test]
+    async fn cheapest_expands_metro_areas() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LON/destination/JFK/cabin/ECONOMY/cheapest";
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        let origins: Vec<&str> = page.content.iter().map(|flight| flight.origin.as_str()).collect();
+        assert!(origins.contains(&"LHR"));
+        assert!(origins.contains(&"LGW"));
+        let points: Vec<i32> = page.content.iter().filter_map(|flight| flight.cabin_points_value(CabinType::Economy)).collect();
+        assert!(points.is_sorted());
+    }
+
+    #[actix_web::test]
+    async fn latest_exports_csv() {
+        let api = mock_api!();
+        let request = test::TestRequest::get().uri(LATEST).insert_header(("Accept", "text/csv")).to_request();
+        let response = test::call_service(&api, request).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "text/csv; charset=utf-8");
+        let body = test::read_body(response).await;
+        let lines: Vec<&str> = std::str::from_utf8(&body).unwrap().lines().collect();
+        assert_eq!(lines[0], RewardFlightCsvRow::HEADERS.join(","));
+        assert_eq!(lines.len(), 8);
+    }
+
+    #[actix_web::test]
+    async fn exports_every_page_as_csv() {
+        let api = mock_api!();
+        let cases = [
+            (format!("{}?format=csv&page-size=2", LATEST), 3),
+            (format!("{}?format=csv&page-size=2&all=true", LATEST), 8),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/cheapest?format=csv&page-size=2&all=true".to_string(), 11),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/on/2026-11-02/historic?format=csv&page-size=2&all=true".to_string(), 6),
+        ];
+        for (uri, line_count) in cases {
+            let response = test::call_service(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            let body = test::read_body(response).await;
+            let lines: Vec<&str> = std::str::from_utf8(&body).unwrap().lines().collect();
+            assert_eq!(lines[0], RewardFlightCsvRow::HEADERS.join(","), "{}", uri);
+            assert_eq!(lines.len(), line_count, "{}", uri);
         }
     }
-}
 
-/// Handler for retrieving historic reward flights for a specific date
-///
-/// # Parameters
-/// * `origin` - The origin airport code (e.g., "LHR")
-/// * `destination` - The destination airport code (e.g., "JFK")
-/// * `on` - The specific date for the flight in YYYY-MM-DD format
-/// * `page-number` - The page number for pagination (default: 0)
-/// * `page-size` - The number of items per page (default: 10)
-///
-/// # Returns
-/// A paginated list of historic reward flights for the specified date ordered by scraped_at ascending
-#[get("/api/v1/airline/vs/reward-flights/origin/{origin}/destination/{destination}/on/{on}/historic")]
-async fn historic_reward_flights(
-    path: web::Path<(String, String, String)>,
-    query: web::Query<PageParams>,
-    repo: web::Data<RewardFlightLatestRepository>,
-) -> impl Responder {
-    let (origin, destination, on) = path.into_inner();
-    let page_number = query.page_number.unwrap_or(0);
-    let page_size = query.page_size.unwrap_or(10);
-    
-    // Parse date
-    let departure_date = match NaiveDate::parse_from_str(&on, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => return HttpResponse::BadRequest().body("Invalid date format. Expected YYYY-MM-DD"),
-    };
+    #[actix_web::test]
+    async fn invalid_requests_are_problems() {
+        let api = mock_api!();
+        let cases = [
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-13-01/to/2026-11-08", StatusCode::BAD_REQUEST, Some("from")),
+            ("/api/v1/airline/vs/reward-flights/origin/XXX/destination/JFK/from/2026-11-01/to/2026-11-08", StatusCode::BAD_REQUEST, Some("origin")),
+            ("/api/v1/airline/zz/reward-flights/origin/LHR/destination/JFK/from/2026-11-01/to/2026-11-08", StatusCode::NOT_FOUND, None),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/COACH/cheapest", StatusCode::BAD_REQUEST, Some("cabin_type")),
+            ("/api/v1/airline/vs/reward-flights/origin/LON/destination/JFK/on/2026-11-02/historic", StatusCode::BAD_REQUEST, Some("origin")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/NYC/on/2026-11-02/price-history", StatusCode::BAD_REQUEST, Some("destination")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/around/+262142-12-30?flex-days=3", StatusCode::BAD_REQUEST, Some("date")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/around/-262143-01-02?flex-days=3", StatusCode::BAD_REQUEST, Some("date")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-01/to/2026-11-08?page-number=-1", StatusCode::BAD_REQUEST, Some("page-number")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-01/to/2026-11-08?page-size=0", StatusCode::BAD_REQUEST, Some("page-size")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-01/to/2026-11-08?page-size=-1", StatusCode::BAD_REQUEST, Some("page-size")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/cheapest?page-size=0", StatusCode::BAD_REQUEST, Some("page-size")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/cheapest?page-number=-1", StatusCode::BAD_REQUEST, Some("page-number")),
+            ("/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-02/to/2026-11-08/changes?page-size=1001", StatusCode::BAD_REQUEST, Some("page-size")),
+            ("/api/v1/webhook-dead-letters?page-size=-5", StatusCode::BAD_REQUEST, Some("page-size")),
+            ("/api/v1/nowhere", StatusCode::NOT_FOUND, None),
+        ];
+        for (uri, status, field) in cases {
+            let response = test::call_service(&api, test::TestRequest::get().uri(uri).to_request()).await;
+            assert_eq!(response.status(), status, "{}", uri);
+            assert_eq!(response.headers().get("Content-Type").unwrap(), "application/problem+json", "{}", uri);
+            let problem: Problem = test::read_body_json(response).await;
+            assert_eq!(problem.status, status.as_u16(), "{}", uri);
+            assert_eq!(problem.errors.first().map(|error| error.field.as_str()), field, "{}", uri);
+        }
+    }
 
-    // Query the repository
-    match repo.find_by_origin_and_destination_and_carrier_code_and_departure_order_by_scraped_at_asc(
-        &origin,
-        &destination,
-        "VS",
-        departure_date,
-        page_number as usize,
-        page_size as usize,
-    ).await {
-        Ok(page) => HttpResponse::Ok().json(page),
-        Err(e) => {
-            log::error!("Database error: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch historic reward flights")
+    #[actix_web::test]
+    async fn malformed_query_is_a_problem() {
+        let api = mock_api!();
+        let uri = format!("{}?page-number=abc", LATEST);
+        let response = test::call_service(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "application/problem+json");
+        let problem: Problem = test::read_body_json(response).await;
+        assert_eq!(problem.errors[0].field, "page-number");
+    }
+
+    #[actix_web::test]
+    async fn cheapest_ranks_first_class() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/FIRST/cheapest";
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        let points: Vec<i32> = page.content.iter().filter_map(|flight| flight.cabin_points_value(CabinType::First)).collect();
+        assert_eq!(points.len(), page.content.len());
+        assert!(!points.is_empty());
+        assert!(points.is_sorted());
+    }
+
+    #[actix_web::test]
+    async fn cheapest_filters_departure_days() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/cheapest?weekends-only=true";
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        let weekdays: Vec<Weekday> = page.content.iter().map(|flight| flight.departure.parse::<NaiveDate>().unwrap().weekday()).collect();
+        assert!(!weekdays.is_empty());
+        assert!(weekdays.iter().all(|weekday| matches!(weekday, Weekday::Sat | Weekday::Sun)));
+    }
+
+    #[actix_web::test]
+    async fn round_trip_filters_outbound_departure_days() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/round-trip?outbound-from=2026-11-02&outbound-to=2026-11-08&return-from=2026-11-09&return-to=2026-11-12&weekdays=THU,FRI";
+        let page: Page<RewardFlightRoundTrip> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        let outbound_weekdays: Vec<Weekday> = page
+            .content
+            .iter()
+            .map(|round_trip| round_trip.outbound.departure.parse::<NaiveDate>().unwrap().weekday())
+            .collect();
+        assert!(!outbound_weekdays.is_empty());
+        assert!(outbound_weekdays.iter().all(|weekday| matches!(weekday, Weekday::Thu | Weekday::Fri)));
+        // Returns on any day of their window
+        assert!(page.content.iter().any(|round_trip| round_trip.inbound.departure == "2026-11-09"));
+    }
+
+    #[actix_web::test]
+    async fn calendar_filters_departure_days() {
+        let api = mock_api!();
+        let calendar = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/calendar/2026-11";
+        let uri = format!("{}?weekends-only=true", calendar);
+        let month: RewardFlightCalendar = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+
+        assert_eq!(month.days.len(), 30);
+        let (weekends, weekdays): (Vec<&CalendarDay>, Vec<&CalendarDay>) = month
+            .days
+            .iter()
+            .partition(|day| matches!(day.date.weekday(), Weekday::Sat | Weekday::Sun));
+        assert!(weekends.iter().all(|day| day.economy.is_some()));
+        assert!(weekdays.iter().all(|day| day.economy.is_none() && day.business.is_none()));
+
+        // The calendar is not paginated
+        let uri = format!("{}?page-size=5", calendar);
+        let response = test::call_service(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn historic_applies_filters() {
+        let api = mock_api!();
+        let historic = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/on/2026-11-02/historic";
+        let cases = [("", 5), ("?cabin=ECONOMY&min-seats=3", 3), ("?cabin=ECONOMY&max-points=10500", 2), ("?weekends-only=true", 0)];
+        for (params, count) in cases {
+            let uri = format!("{}{}", historic, params);
+            let page: Page<RewardFlightLatestHistoric> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+            assert_eq!(page.content.len(), count, "{}", uri);
+            assert!(page.content.is_sorted_by_key(|flight| flight.scraped_at), "{}", uri);
         }
     }
-}
 
-// Query parameters for pagination
-#[derive(Debug, Deserialize)]
-struct PageParams {
-    #[serde(rename = "page-number")]
-    page_number: Option<i32>,
-    #[serde(rename = "page-size")]
-    page_size: Option<i32>,
-}
+    #[actix_web::test]
+    async fn flexible_returns_the_best_option_per_day() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/around/2026-11-05?flex-days=3&weekends-only=true";
+        let search: FlexibleDateSearch = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
 
-// Enum for cabin types
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-enum CabinType {
-    Economy,
-    PremiumEconomy,
-    Business,
-}
+        // 2026-11-07 and 2026-11-08 are the only weekend days of the window, at equal points
+        let days_from_target: Vec<i64> = search.options.iter().map(|option| option.days_from_target).collect();
+        assert_eq!(days_from_target, vec![2, 3]);
+        assert!(search.options.iter().all(|option| option.flights_available == 1 && option.cabin_points_value == 10000));
+    }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    // Initialize environment
-    dotenv().ok();
-    env_logger::init();
+    #[actix_web::test]
+    async fn export_streams_ndjson() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/export?origin=LHR&destination=JFK&from=2026-11-02&to=2026-11-04";
+        let response = test::call_service(&api, test::TestRequest::get().uri(uri).to_request()).await;
 
-    info!("Starting server at http://127.0.0.1:8086");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "application/x-ndjson");
+        let body = test::read_body(response).await;
+        let flights: Vec<RewardFlightLatest> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(flights.len(), 3);
+    }
 
-    // Get database URL from environment
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set in .env file");
-    
-    // Create database connection pool and test connection
-    info!("Testing database connection...");
-    let pool = match sqlx::postgres::PgPool::connect(&database_url).await {
-        Ok(pool) => {
-            // Test the connection by executing a simple query
-            match sqlx::query("SELECT 1").execute(&pool).await {
-                Ok(_) => {
-                    info!("Database connection successful");
-                    pool
-                },
-                Err(e) => {
-                    log::error!("Database connection test failed: {}", e);
-                    panic!("Failed to connect to database: {}", e);
-                }
-            }
-        },
-        Err(e) => {
-            log::error!("Failed to create database connection pool: {}", e);
-            panic!("Failed to create database connection pool: {}", e);
+    #[actix_web::test]
+    async fn search_endpoints_respond() {
+        let api = mock_api!();
+        let uris = [
+            "/health",
+            "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/on/2026-11-02/historic",
+            "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/from/2026-11-02/to/2026-11-08/changes",
+            "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/calendar/2026-11",
+        ];
+        for uri in uris {
+            let response = test::call_service(&api, test::TestRequest::get().uri(uri).to_request()).await;
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
         }
-    };
+    }
 
-    // Create repository with database connection
-    let repository = web::Data::new(RewardFlightLatestRepository::new(pool));
+    #[actix_web::test]
+    async fn explore_returns_the_cheapest_flight_per_destination() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/cabin/BUSINESS/from/2026-11-02/to/2026-11-08/explore";
+        let page: Page<RewardFlightLatest> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
 
-    // Start HTTP server
-    HttpServer::new(move || {
-        App::new()
-            .app_data(repository.clone())
-            .service(health_check)
-            .service(latest_reward_flights)
-            .service(cheapest_reward_flights)
-            .service(historic_reward_flights)
-    })
-    .bind("0.0.0.0:8086")?
-    .run()
-    .await
+        let destinations: Vec<&str> = page.content.iter().map(|flight| flight.destination.as_str()).collect();
+        assert_eq!(destinations, vec!["BGI", "MCO", "BOS", "LAX", "JFK"]);
+        assert_eq!(page.total_elements, 5);
+        let points: Vec<i32> = page.content.iter().filter_map(|flight| flight.cabin_points_value(CabinType::Business)).collect();
+        assert_eq!(points.len(), 5);
+        assert!(points.is_sorted());
+        assert!(page.content.iter().all(|flight| flight.origin == "LHR" && ("2026-11-02"..="2026-11-08").contains(&flight.departure.as_str())));
+    }
+
+    #[actix_web::test]
+    async fn round_trip_pairs_flights_within_the_trip_length() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/ECONOMY/round-trip?outbound-from=2026-11-02&outbound-to=2026-11-04&return-from=2026-11-09&return-to=2026-11-12&min-trip-days=7&max-trip-days=8&return-cabin=BUSINESS&page-size=100";
+        let page: Page<RewardFlightRoundTrip> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        // Each of the three outbound days pairs with the returns 7 and 8 days later
+        assert_eq!(page.total_elements, 6);
+        assert!(page.content.iter().all(|round_trip| (7..=8).contains(&round_trip.trip_length_days)));
+        assert!(page.content.iter().all(|round_trip| {
+            round_trip.outbound.origin == "LHR" && round_trip.inbound.origin == "JFK" && round_trip.return_cabin == CabinType::Business
+        }));
+        let total_points: Vec<i32> = page.content.iter().map(|round_trip| round_trip.total_points).collect();
+        assert!(total_points.is_sorted());
+        assert!(total_points.iter().all(|&points| points == 10000 + 30000));
+    }
+
+    #[actix_web::test]
+    async fn price_history_downsamples_by_interval() {
+        let api = mock_api!();
+        let price_history = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/on/2026-11-02/price-history";
+
+        // The five hourly mock snapshots of the last day
+        let uri = format!("{}?interval=hourly", price_history);
+        let history: PriceHistory = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        let series_lengths: Vec<(CabinType, usize)> = history.cabins.iter().map(|cabin| (cabin.cabin_type, cabin.series.len())).collect();
+        assert_eq!(series_lengths, vec![(CabinType::Economy, 5), (CabinType::PremiumEconomy, 5), (CabinType::Business, 5), (CabinType::First, 0)]);
+
+        // Falling into the one or two days they span
+        let uri = format!("{}?interval=daily", price_history);
+        let history: PriceHistory = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        let economy = &history.cabins[0].series;
+        assert!((1..=2).contains(&economy.len()));
+        assert!(economy.iter().all(|point| point.scraped_at.time() == chrono::NaiveTime::MIN));
+        assert_eq!(economy.iter().filter_map(|point| point.points).min(), Some(10000));
+    }
+
+    #[actix_web::test]
+    async fn statistics_summarise_the_route() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/BUSINESS/statistics";
+        let statistics: RouteStatistics = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        // Five snapshots priced 30000 to 34000 for each of the next seven departures
+        assert_eq!(statistics.snapshot_count, 35);
+        assert_eq!(statistics.departure_count, 7);
+        assert_eq!(
+            (statistics.min_points, statistics.median_points, statistics.p90_points, statistics.max_points),
+            (Some(30000), Some(32000), Some(34000), Some(34000))
+        );
+        assert_eq!(statistics.saver_departure_share, Some(0.0));
+        assert_eq!(statistics.average_seats, Some(2.0));
+    }
+
+    #[actix_web::test]
+    async fn release_pattern_summarises_releases_and_withdrawals() {
+        let api = mock_api!();
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destination/JFK/cabin/BUSINESS/release-pattern";
+        let pattern: ReleasePattern = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+
+        assert_eq!(pattern.cabin_type, CabinType::Business);
+        let released = &pattern.released;
+        assert_eq!(
+            (released.event_count, released.min_days_before, released.median_days_before, released.max_days_before),
+            (33, Some(13), Some(330), Some(331))
+        );
+        let withdrawn = &pattern.withdrawn;
+        assert_eq!((withdrawn.event_count, withdrawn.median_days_before), (16, Some(7)));
+    }
+
+    #[actix_web::test]
+    async fn discovery_lists_carriers_origins_and_destinations() {
+        let api = mock_api!();
+        let summary = |summaries: &[AvailabilitySummary]| -> Vec<(String, i64, bool)> {
+            summaries.iter().map(|summary| (summary.code.clone(), summary.flight_count, summary.name.is_some())).collect()
+        };
+
+        let carriers: Vec<AvailabilitySummary> = test::call_and_read_body_json(&api, test::TestRequest::get().uri("/api/v1/airlines").to_request()).await;
+        assert_eq!(summary(&carriers), vec![("VS".to_string(), 1200, true)]);
+
+        let uri = "/api/v1/airline/vs/reward-flights/origins";
+        let origins: Vec<AvailabilitySummary> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(summary(&origins), vec![
+            ("LGW".to_string(), 150, true),
+            ("LHR".to_string(), 900, true),
+            ("MAN".to_string(), 150, true),
+        ]);
+
+        let uri = "/api/v1/airline/vs/reward-flights/origin/LHR/destinations";
+        let destinations: Vec<AvailabilitySummary> = test::call_and_read_body_json(&api, test::TestRequest::get().uri(uri).to_request()).await;
+        let codes: Vec<&str> = destinations.iter().map(|summary| summary.code.as_str()).collect();
+        assert_eq!(codes, vec!["BOS", "JFK", "LAX"]);
+        assert_eq!(destinations.iter().map(|summary| summary.flight_count).sum::<i64>(), 720);
+    }
+
+    #[actix_web::test]
+    async fn saved_searches_round_trip() {
+        let api = mock_api!();
+        let request = serde_json::json!({
+            "name": " Christmas in New York ",
+            "carrier_code": "vs",
+            "origin": "lon",
+            "destination": "JFK",
+            "departure_from": "2026-12-20",
+            "departure_to": "2026-12-24",
+            "cabin_type": "BUSINESS",
+            "min_seats": 2,
+            "max_points": null,
+            "webhook_url": null,
+            "webhook_secret": null,
+        });
+
+        let response = test::call_service(&api, test::TestRequest::post().uri("/api/v1/saved-searches").set_json(&request).to_request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let saved_search: SavedSearch = test::read_body_json(response).await;
+        assert_eq!(saved_search.name, "Christmas in New York");
+        assert_eq!(saved_search.carrier_code, "VS");
+        assert_eq!(saved_search.origin, "LON");
+
+        let uri = format!("/api/v1/saved-searches/{}", saved_search.id);
+        let found: SavedSearch = test::call_and_read_body_json(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(found.id, saved_search.id);
+
+        let matches_uri = format!("{}/matches", uri);
+        let response = test::call_service(&api, test::TestRequest::get().uri(&matches_uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = test::call_service(&api, test::TestRequest::delete().uri(&uri).to_request()).await;
+        assert!(response.status().is_success());
+        let response = test::call_service(&api, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn saved_search_webhooks_must_be_public() {
+        let api = mock_api!();
+        let request = serde_json::json!({
+            "name": "Metadata",
+            "carrier_code": "VS",
+            "origin": "LHR",
+            "destination": "JFK",
+            "departure_from": "2026-12-20",
+            "departure_to": "2026-12-24",
+            "cabin_type": "BUSINESS",
+            "min_seats": null,
+            "max_points": null,
+            "webhook_url": "http://169.254.169.254/latest/meta-data/",
+            "webhook_secret": "s3cret",
+        });
+
+        let response = test::call_service(&api, test::TestRequest::post().uri("/api/v1/saved-searches").set_json(&request).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let problem: Problem = test::read_body_json(response).await;
+        assert_eq!(problem.errors[0].field, "webhook_url");
+    }
+
+    #[actix_web::test]
+    async fn replaying_unknown_dead_letters_is_not_found() {
+        let api = mock_api!();
+        let response = test::call_service(&api, test::TestRequest::post().uri("/api/v1/webhook-dead-letters/1/replay").to_request()).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "application/problem+json");
+    }
+
+    #[actix_web::test]
+    async fn invalid_saved_searches_are_rejected() {
+        let api = mock_api!();
+        let request = serde_json::json!({
+            "name": "Backwards",
+            "carrier_code": "VS",
+            "origin": "LHR",
+            "destination": "JFK",
+            "departure_from": "2026-12-24",
+            "departure_to": "2026-12-20",
+            "cabin_type": "BUSINESS",
+            "min_seats": null,
+            "max_points": null,
+            "webhook_url": null,
+            "webhook_secret": null,
+        });
+
+        let response = test::call_service(&api, test::TestRequest::post().uri("/api/v1/saved-searches").set_json(&request).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let problem: Problem = test::read_body_json(response).await;
+        assert_eq!(problem.errors[0].field, "departure_from");
+    }
 }